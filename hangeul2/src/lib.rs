@@ -1,10 +1,19 @@
 
 use std::char;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
 
 const BLOCK_START: u32 = 0xAC00;
 const CONSONANT_START: u32 = 0x3130;
 const VOWEL_START: u32 = 0x314F;
 
+// Conjoining jamo (U+1100 - U+11FF), as used by decomposed (NFD) text
+const CONJOINING_INITIAL_START: u32 = 0x1100;
+const CONJOINING_VOWEL_START: u32 = 0x1161;
+const CONJOINING_FINAL_START: u32 = 0x11A7;
+
+const NUM_INITIALS: u32 = 19;
 const NUM_FINALS: u32 = 28;
 const NUM_VOWELS: u32 = 21;
 
@@ -59,6 +68,33 @@ impl Initial {
         }
     }
     
+    /// Returns the initial at the given offset, as used by `offset`.
+    pub fn from_offset(offset: u32) -> Option<Initial> {
+        use self::Initial::*;
+        match offset {
+            0  => Some(G),
+            1  => Some(Gg),
+            2  => Some(N),
+            3  => Some(D),
+            4  => Some(Dd),
+            5  => Some(R),
+            6  => Some(M),
+            7  => Some(B),
+            8  => Some(Bb),
+            9  => Some(S),
+            10 => Some(Ss),
+            11 => Some(Ieung),
+            12 => Some(J),
+            13 => Some(Jj),
+            14 => Some(Ch),
+            15 => Some(K),
+            16 => Some(T),
+            17 => Some(P),
+            18 => Some(H),
+            _  => None,
+        }
+    }
+    
     fn char_offset(&self) -> u32 {
         use self::Initial::*;
         match *self {
@@ -91,6 +127,50 @@ impl Initial {
             .expect("hangeul2 constructed an invalid hangeul character!");
         res
     }
+    
+    /// Returns the initial written as the given compatibility jamo
+    /// (U+3131 - U+314E), as returned by `as_char`.
+    pub fn from_char(ch: char) -> Option<Initial> {
+        (0..NUM_INITIALS).filter_map(Initial::from_offset).find(|ini| ini.as_char() == ch)
+    }
+    
+    /// Returns the conjoining jamo of the initial (U+1100 - U+1112).
+    pub fn as_conjoining_char(&self) -> char {
+        char::from_u32(CONJOINING_INITIAL_START + self.offset())
+            .expect("hangeul2 constructed an invalid hangeul character!")
+    }
+    
+    /// Returns the initial of the given conjoining jamo (U+1100 - U+1112).
+    pub fn from_conjoining_char(ch: char) -> Option<Initial> {
+        (ch as u32).checked_sub(CONJOINING_INITIAL_START).and_then(Initial::from_offset)
+    }
+    
+    /// Returns the final written with the same consonant, or `None` for ㄸ,
+    /// ㅃ and ㅉ, which are never finals.
+    pub fn as_final(&self) -> Option<Final> {
+        use self::Initial::*;
+        match *self {
+            G     => Some(Final::G),
+            Gg    => Some(Final::Gg),
+            N     => Some(Final::N),
+            D     => Some(Final::D),
+            Dd    => None,
+            R     => Some(Final::L),
+            M     => Some(Final::M),
+            B     => Some(Final::B),
+            Bb    => None,
+            S     => Some(Final::S),
+            Ss    => Some(Final::Ss),
+            Ieung => Some(Final::Ieung),
+            J     => Some(Final::J),
+            Jj    => None,
+            Ch    => Some(Final::Ch),
+            K     => Some(Final::K),
+            T     => Some(Final::T),
+            P     => Some(Final::P),
+            H     => Some(Final::H),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Hash)]
@@ -145,6 +225,35 @@ impl Vowel {
         }
     }
     
+    /// Returns the vowel at the given offset, as used by `offset`.
+    pub fn from_offset(offset: u32) -> Option<Vowel> {
+        use self::Vowel::*;
+        match offset {
+            0  => Some(A),
+            1  => Some(Ae),
+            2  => Some(Ya),
+            3  => Some(Yae),
+            4  => Some(Eo),
+            5  => Some(E),
+            6  => Some(Yeo),
+            7  => Some(Ye),
+            8  => Some(O),
+            9  => Some(Wa),
+            10 => Some(Wae),
+            11 => Some(Oe),
+            12 => Some(Yo),
+            13 => Some(U),
+            14 => Some(Weo),
+            15 => Some(We),
+            16 => Some(Wi),
+            17 => Some(Yu),
+            18 => Some(Y),
+            19 => Some(Yi),
+            20 => Some(I),
+            _  => None,
+        }
+    }
+    
     pub fn as_char(&self) -> char {
         let mut code = VOWEL_START;
         code += self.offset();
@@ -152,6 +261,48 @@ impl Vowel {
             .expect("hangeul2 constructed an invalid hangeul character!");
         res
     }
+    
+    /// Returns the vowel written as the given compatibility jamo
+    /// (U+314F - U+3163), as returned by `as_char`.
+    pub fn from_char(ch: char) -> Option<Vowel> {
+        (ch as u32).checked_sub(VOWEL_START).and_then(Vowel::from_offset)
+    }
+    
+    /// Returns the conjoining jamo of the vowel (U+1161 - U+1175).
+    pub fn as_conjoining_char(&self) -> char {
+        char::from_u32(CONJOINING_VOWEL_START + self.offset())
+            .expect("hangeul2 constructed an invalid hangeul character!")
+    }
+    
+    /// Returns the vowel of the given conjoining jamo (U+1161 - U+1175).
+    pub fn from_conjoining_char(ch: char) -> Option<Vowel> {
+        (ch as u32).checked_sub(CONJOINING_VOWEL_START).and_then(Vowel::from_offset)
+    }
+    
+    /// Splits a compound vowel into the two vowels it is written with, or
+    /// returns the vowel and `None` if it is a single one.
+    ///
+    /// Ex: `Wa` => `(O, Some(A))`
+    pub fn split(&self) -> (Vowel, Option<Vowel>) {
+        use self::Vowel::*;
+        match *self {
+            Wa    => (O, Some(A)),
+            Wae   => (O, Some(Ae)),
+            Oe    => (O, Some(I)),
+            Weo   => (U, Some(Eo)),
+            We    => (U, Some(E)),
+            Wi    => (U, Some(I)),
+            Yi    => (Y, Some(I)),
+            vowel => (vowel, None),
+        }
+    }
+    
+    /// Joins two vowels into the compound vowel that they make, if any.
+    ///
+    /// Ex: `(O, A)` => `Some(Wa)`
+    pub fn join(first: Vowel, second: Vowel) -> Option<Vowel> {
+        (0..NUM_VOWELS).filter_map(Vowel::from_offset).find(|vowel| vowel.split() == (first, Some(second)))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Hash)]
@@ -220,6 +371,42 @@ impl Final {
         }
     }
     
+    /// Returns the final at the given offset, as used by `offset`.
+    pub fn from_offset(offset: u32) -> Option<Final> {
+        use self::Final::*;
+        match offset {
+            0  => Some(Empty),
+            1  => Some(G),
+            2  => Some(Gg),
+            3  => Some(Gs),
+            4  => Some(N),
+            5  => Some(Nj),
+            6  => Some(Nh),
+            7  => Some(D),
+            8  => Some(L),
+            9  => Some(Lg),
+            10 => Some(Lm),
+            11 => Some(Lb),
+            12 => Some(Ls),
+            13 => Some(Lt),
+            14 => Some(Lp),
+            15 => Some(Lh),
+            16 => Some(M),
+            17 => Some(B),
+            18 => Some(Bs),
+            19 => Some(S),
+            20 => Some(Ss),
+            21 => Some(Ieung),
+            22 => Some(J),
+            23 => Some(Ch),
+            24 => Some(K),
+            25 => Some(T),
+            26 => Some(P),
+            27 => Some(H),
+            _  => None,
+        }
+    }
+    
     
     
     fn char_offset(&self) -> u32 {
@@ -263,6 +450,119 @@ impl Final {
             .expect("hangeul2 constructed an invalid hangeul character!");
         res
     }
+    
+    /// Returns the (non-empty) final written as the given compatibility jamo
+    /// (U+3131 - U+314E), as returned by `as_char`.
+    pub fn from_char(ch: char) -> Option<Final> {
+        (1..NUM_FINALS).filter_map(Final::from_offset).find(|fin| fin.as_char() == ch)
+    }
+    
+    /// Returns the conjoining jamo of the final (U+11A8 - U+11C2), or `None`
+    /// for `Empty`.
+    pub fn as_conjoining_char(&self) -> Option<char> {
+        match *self {
+            Final::Empty => None,
+            fin => char::from_u32(CONJOINING_FINAL_START + fin.offset()),
+        }
+    }
+    
+    /// Returns the final of the given conjoining jamo (U+11A8 - U+11C2).
+    pub fn from_conjoining_char(ch: char) -> Option<Final> {
+        match (ch as u32).checked_sub(CONJOINING_FINAL_START) {
+            Some(0) | None => None,
+            Some(offset) => Final::from_offset(offset),
+        }
+    }
+    
+    /// Splits a compound final into the final that is written first and the
+    /// consonant after it, or returns the final and `None` if it is a single
+    /// (or double) consonant.
+    ///
+    /// Ex: `Lg` => `(L, Some(Initial::G))`, `Gg` => `(Gg, None)`
+    pub fn split(&self) -> (Final, Option<Initial>) {
+        use self::Final::*;
+        match *self {
+            Gs  => (G, Some(Initial::S)),
+            Nj  => (N, Some(Initial::J)),
+            Nh  => (N, Some(Initial::H)),
+            Lg  => (L, Some(Initial::G)),
+            Lm  => (L, Some(Initial::M)),
+            Lb  => (L, Some(Initial::B)),
+            Ls  => (L, Some(Initial::S)),
+            Lt  => (L, Some(Initial::T)),
+            Lp  => (L, Some(Initial::P)),
+            Lh  => (L, Some(Initial::H)),
+            Bs  => (B, Some(Initial::S)),
+            fin => (fin, None),
+        }
+    }
+    
+    /// Joins a final and a following consonant into the final that they
+    /// make, if any. The consonant is joined to an empty final on its own.
+    ///
+    /// Ex: `(L, Initial::G)` => `Some(Lg)`, `(Empty, Initial::G)` => `Some(G)`
+    pub fn join(first: Final, second: Initial) -> Option<Final> {
+        if first == Final::Empty {
+            return second.as_final();
+        }
+        (1..NUM_FINALS).filter_map(Final::from_offset).find(|fin| fin.split() == (first, Some(second)))
+    }
+    
+    /// Returns the initial written with the same consonant, or `None` for
+    /// `Empty` and the compound finals.
+    pub fn as_initial(&self) -> Option<Initial> {
+        (0..NUM_INITIALS).filter_map(Initial::from_offset).find(|ini| ini.as_final() == Some(*self))
+    }
+}
+
+/// The error of converting between initials and finals that are not
+/// written with the same consonant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NoSuchJamo;
+
+impl fmt::Display for NoSuchJamo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The consonant is not used in that position")
+    }
+}
+
+impl Error for NoSuchJamo {}
+
+impl TryFrom<Initial> for Final {
+    type Error = NoSuchJamo;
+
+    fn try_from(ini: Initial) -> Result<Final, NoSuchJamo> {
+        ini.as_final().ok_or(NoSuchJamo)
+    }
+}
+
+impl TryFrom<Final> for Initial {
+    type Error = NoSuchJamo;
+
+    fn try_from(fin: Final) -> Result<Initial, NoSuchJamo> {
+        fin.as_initial().ok_or(NoSuchJamo)
+    }
+}
+
+/// Converts a conjoining jamo (U+1100 - U+11FF) of modern Korean into the
+/// compatibility jamo (U+3131 - U+3163) that is written the same way.
+pub fn to_compatibility_jamo(ch: char) -> Option<char> {
+    Initial::from_conjoining_char(ch).map(|ini| ini.as_char())
+        .or_else(|| Vowel::from_conjoining_char(ch).map(|vowel| vowel.as_char()))
+        .or_else(|| Final::from_conjoining_char(ch).map(|fin| fin.as_char()))
+}
+
+/// Converts a compatibility jamo (U+3131 - U+3163) into a conjoining jamo
+/// (U+1100 - U+11FF), as an initial if `fin` is false, and as a final if
+/// it is true. Vowels are converted in either case.
+pub fn to_conjoining_jamo(ch: char, fin: bool) -> Option<char> {
+    if let Some(vowel) = Vowel::from_char(ch) {
+        Some(vowel.as_conjoining_char())
+    } else if fin {
+        Final::from_char(ch).and_then(|fin| fin.as_conjoining_char())
+    } else {
+        Initial::from_char(ch).map(|ini| ini.as_conjoining_char())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Hash)]
@@ -281,6 +581,19 @@ impl Block {
         }
     }
     
+    /// Splits a precomposed syllable (U+AC00 - U+D7A3) into its parts.
+    pub fn from_char(ch: char) -> Option<Block> {
+        let code = ch as u32;
+        if !(BLOCK_START..BLOCK_START + NUM_INITIALS * CHARS_PER_INITIAL).contains(&code) {
+            return None;
+        }
+        let index = code - BLOCK_START;
+        let initial = Initial::from_offset(index / CHARS_PER_INITIAL)?;
+        let vowel = Vowel::from_offset((index % CHARS_PER_INITIAL) / CHARS_PER_VOWEL)?;
+        let fin = Final::from_offset(index % CHARS_PER_VOWEL)?;
+        Some(Block::from_parts(initial, vowel, fin))
+    }
+    
     pub fn combine(&self) -> char {
         let mut code = BLOCK_START;
         code += self.initial.offset() * CHARS_PER_INITIAL;
@@ -291,3 +604,510 @@ impl Block {
         res
     }
 }
+
+/// Decomposes the precomposed syllables of the given text into conjoining
+/// jamo, as in the canonical decomposition (NFD) of Unicode. Other
+/// characters are kept as they are.
+pub fn decompose(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match Block::from_char(ch) {
+            Some(block) => {
+                s.push(block.initial.as_conjoining_char());
+                s.push(block.vowel.as_conjoining_char());
+                s.extend(block.fin.as_conjoining_char());
+            }
+            None => s.push(ch),
+        }
+    }
+    s
+}
+
+/// Composes the conjoining jamo of the given text into precomposed
+/// syllables, as in the canonical composition (NFC) of Unicode. An initial
+/// and a vowel make a syllable, which a following final is added to.
+pub fn compose(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+    let mut last: Option<char> = None;
+    for ch in text.chars() {
+        let composed = last.and_then(|last| {
+            if let (Some(ini), Some(vowel)) = (Initial::from_conjoining_char(last), Vowel::from_conjoining_char(ch)) {
+                return Some(Block::from_parts(ini, vowel, Final::Empty).combine());
+            }
+            let block = Block::from_char(last).filter(|block| block.fin == Final::Empty)?;
+            let fin = Final::from_conjoining_char(ch)?;
+            Some(Block::from_parts(block.initial, block.vowel, fin).combine())
+        });
+        match composed {
+            Some(syllable) => {
+                s.pop();
+                s.push(syllable);
+                last = Some(syllable);
+            }
+            None => {
+                s.push(ch);
+                last = Some(ch);
+            }
+        }
+    }
+    s
+}
+
+/// The kinds of 한글 characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HangulKind {
+    /// A precomposed syllable (U+AC00 - U+D7A3).
+    Syllable,
+    /// A consonant of Hangul Compatibility Jamo (U+3131 - U+314E and the
+    /// archaic U+3165 - U+3186).
+    CompatibilityConsonant,
+    /// A vowel of Hangul Compatibility Jamo (U+314F - U+3163 and the archaic
+    /// U+3187 - U+318E).
+    CompatibilityVowel,
+    /// A conjoining initial (U+1100 - U+115F and U+A960 - U+A97C).
+    ConjoiningInitial,
+    /// A conjoining vowel (U+1160 - U+11A7 and U+D7B0 - U+D7C6).
+    ConjoiningVowel,
+    /// A conjoining final (U+11A8 - U+11FF and U+D7CB - U+D7FB).
+    ConjoiningFinal,
+    /// A halfwidth jamo (U+FFA0 - U+FFDC).
+    Halfwidth,
+    /// Any other character.
+    Other,
+}
+
+/// Returns what kind of 한글 character the given character is.
+pub fn classify(ch: char) -> HangulKind {
+    use self::HangulKind::*;
+    match ch as u32 {
+        0xAC00..=0xD7A3 => Syllable,
+        0x3131..=0x314E | 0x3165..=0x3186 => CompatibilityConsonant,
+        0x314F..=0x3163 | 0x3187..=0x318E => CompatibilityVowel,
+        0x1100..=0x115F | 0xA960..=0xA97C => ConjoiningInitial,
+        0x1160..=0x11A7 | 0xD7B0..=0xD7C6 => ConjoiningVowel,
+        0x11A8..=0x11FF | 0xD7CB..=0xD7FB => ConjoiningFinal,
+        0xFFA0 => Halfwidth,
+        _ if from_halfwidth(ch).is_some() => Halfwidth,
+        _ => Other,
+    }
+}
+
+/// Returns whether the given character is a 한글 syllable or jamo.
+pub fn is_hangeul(ch: char) -> bool {
+    classify(ch) != HangulKind::Other
+}
+
+/// Returns whether the given character is a precomposed 한글 syllable.
+pub fn is_syllable(ch: char) -> bool {
+    classify(ch) == HangulKind::Syllable
+}
+
+/// Returns whether the given character is a 한글 jamo of any kind.
+pub fn is_jamo(ch: char) -> bool {
+    !matches!(classify(ch), HangulKind::Syllable | HangulKind::Other)
+}
+
+/// The initials of the circled and parenthesized jamo and syllables
+/// (U+3200 - U+321B and U+3260 - U+327B), in order.
+const ENCLOSED_INITIALS: [Initial; 14] = [
+    Initial::G, Initial::N, Initial::D, Initial::R, Initial::M, Initial::B, Initial::S,
+    Initial::Ieung, Initial::J, Initial::Ch, Initial::K, Initial::T, Initial::P, Initial::H,
+];
+
+/// Returns the compatibility jamo of the given halfwidth jamo
+/// (U+FFA1 - U+FFDC).
+fn from_halfwidth(ch: char) -> Option<char> {
+    let code = ch as u32;
+    match code {
+        0xFFA1..=0xFFBE => char::from_u32(code - 0xFFA1 + CONSONANT_START + 1),
+        0xFFC2..=0xFFDC => {
+            // The vowels come in rows of six, with two unused code points
+            // after each row
+            let index = code - 0xFFC2;
+            if index % 8 < 6 {
+                char::from_u32(VOWEL_START + index / 8 * 6 + index % 8)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the compatibility jamo of the given archaic jamo of Hangul Jamo
+/// Extended-A or -B (U+A960 - U+A97C, U+D7B0 - U+D7FB), for the ones that
+/// have one.
+fn from_extended(ch: char) -> Option<char> {
+    match ch {
+        '\u{A964}' => Some('ㄺ'),
+        '\u{A966}' => Some('ㅪ'),
+        '\u{A968}' => Some('ㄻ'),
+        '\u{A969}' => Some('ㄼ'),
+        '\u{A96C}' => Some('ㄽ'),
+        '\u{A971}' => Some('ㅯ'),
+        '\u{D7CD}' => Some('ㄸ'),
+        '\u{D7E3}' => Some('ㅳ'),
+        '\u{D7E6}' => Some('ㅃ'),
+        '\u{D7E7}' => Some('ㅵ'),
+        '\u{D7E8}' => Some('ㅶ'),
+        '\u{D7EF}' => Some('ㅾ'),
+        '\u{D7F9}' => Some('ㅉ'),
+        _           => None,
+    }
+}
+
+/// Adds the 한글 of the given circled or parenthesized jamo or syllables,
+/// and returns whether the character was one.
+fn push_enclosed(ch: char, s: &mut String) -> bool {
+    let code = ch as u32;
+    let letter = |index: u32| ENCLOSED_INITIALS[index as usize % 14];
+    let syllable = |index: u32| Block::from_parts(letter(index), Vowel::A, Final::Empty).combine();
+    match code {
+        0x3200..=0x320D => s.extend(&['(', letter(code - 0x3200).as_char(), ')']),
+        0x320E..=0x321B => s.extend(&['(', syllable(code - 0x320E), ')']),
+        0x321C => s.push_str("(주)"),
+        0x321D => s.push_str("(오전)"),
+        0x321E => s.push_str("(오후)"),
+        0x3260..=0x326D => s.push(letter(code - 0x3260).as_char()),
+        0x326E..=0x327B => s.push(syllable(code - 0x326E)),
+        0x327C => s.push_str("참고"),
+        0x327D => s.push_str("주의"),
+        0x327E => s.push('우'),
+        _ => return false,
+    }
+    true
+}
+
+/// Replaces the variants of 한글 jamo and syllables in the given text with
+/// the ones that the rest of this crate understands:
+///
+/// - Halfwidth jamo (U+FFA1 - U+FFDC) become compatibility jamo.
+/// - Circled jamo and syllables (`㉠`, `㉮`) lose their circle, and
+///   parenthesized ones (`㈀`, `㈎`) are written with parentheses.
+/// - Jamo of Hangul Jamo Extended-A and -B become the compatibility jamo of
+///   the same letter, where there is one (`ꥤ` => `ㄺ`). The others are kept,
+///   since they have no modern equivalent.
+///
+/// Other characters are kept as they are.
+pub fn normalize_jamo_variants(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if let Some(jamo) = from_halfwidth(ch).or_else(|| from_extended(ch)) {
+            s.push(jamo);
+        } else if push_enclosed(ch, &mut s) {
+            continue;
+        } else {
+            s.push(ch);
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use std::char;
+    use std::convert::TryFrom;
+    use super::NoSuchJamo;
+    use super::{Initial, Vowel, Final, Block, to_compatibility_jamo, to_conjoining_jamo};
+    use super::{compose, decompose, normalize_jamo_variants};
+    use super::{classify, is_hangeul, is_jamo, is_syllable};
+
+    #[test]
+    fn offsets_round_trip() {
+        for offset in 0..19 {
+            assert_eq!(Initial::from_offset(offset).unwrap().offset(), offset);
+        }
+        for offset in 0..21 {
+            assert_eq!(Vowel::from_offset(offset).unwrap().offset(), offset);
+        }
+        for offset in 0..28 {
+            assert_eq!(Final::from_offset(offset).unwrap().offset(), offset);
+        }
+        assert_eq!(Initial::from_offset(19), None);
+        assert_eq!(Vowel::from_offset(21), None);
+        assert_eq!(Final::from_offset(28), None);
+    }
+
+    #[test]
+    fn from_char_inverts_combine() {
+        for code in 0xAC00..0xD7A4 {
+            let ch = char::from_u32(code).unwrap();
+            let block = Block::from_char(ch)
+                .unwrap_or_else(|| panic!("Could not decompose '{}'", ch));
+            assert_eq!(block.combine(), ch);
+        }
+    }
+
+    #[test]
+    fn from_char_rejects_non_syllables() {
+        for &ch in &['a', 'ㄱ', 'ㅏ', '\u{ABFF}', '\u{D7A4}', '\u{1100}'] {
+            assert_eq!(Block::from_char(ch), None);
+        }
+        let han = Block::from_parts(Initial::H, Vowel::A, Final::N);
+        assert_eq!(Block::from_char('한'), Some(han));
+    }
+
+    #[test]
+    fn conjoining_jamo_round_trip() {
+        for offset in 0..19 {
+            let ini = Initial::from_offset(offset).unwrap();
+            assert_eq!(Initial::from_conjoining_char(ini.as_conjoining_char()), Some(ini));
+            assert_eq!(Initial::from_char(ini.as_char()), Some(ini));
+        }
+        for offset in 0..21 {
+            let vowel = Vowel::from_offset(offset).unwrap();
+            assert_eq!(Vowel::from_conjoining_char(vowel.as_conjoining_char()), Some(vowel));
+            assert_eq!(Vowel::from_char(vowel.as_char()), Some(vowel));
+        }
+        for offset in 1..28 {
+            let fin = Final::from_offset(offset).unwrap();
+            assert_eq!(Final::from_conjoining_char(fin.as_conjoining_char().unwrap()), Some(fin));
+            assert_eq!(Final::from_char(fin.as_char()), Some(fin));
+        }
+        assert_eq!(Final::Empty.as_conjoining_char(), None);
+    }
+
+    #[test]
+    fn conjoining_jamo() {
+        assert_eq!(Initial::G.as_conjoining_char(), '\u{1100}');
+        assert_eq!(Initial::H.as_conjoining_char(), '\u{1112}');
+        assert_eq!(Vowel::A.as_conjoining_char(), '\u{1161}');
+        assert_eq!(Vowel::I.as_conjoining_char(), '\u{1175}');
+        assert_eq!(Final::G.as_conjoining_char(), Some('\u{11A8}'));
+        assert_eq!(Final::H.as_conjoining_char(), Some('\u{11C2}'));
+        for &ch in &['\u{10FF}', '\u{1113}', '\u{1160}', '\u{11A7}', '\u{11C3}', 'ㄱ'] {
+            assert_eq!(Initial::from_conjoining_char(ch), None);
+            assert_eq!(Vowel::from_conjoining_char(ch), None);
+            assert_eq!(Final::from_conjoining_char(ch), None);
+        }
+    }
+
+    #[test]
+    fn jamo_conversion() {
+        assert_eq!(to_compatibility_jamo('\u{1100}'), Some('ㄱ'));
+        assert_eq!(to_compatibility_jamo('\u{11A8}'), Some('ㄱ'));
+        assert_eq!(to_compatibility_jamo('\u{11AA}'), Some('ㄳ'));
+        assert_eq!(to_compatibility_jamo('\u{1161}'), Some('ㅏ'));
+        assert_eq!(to_compatibility_jamo('a'), None);
+        assert_eq!(to_conjoining_jamo('ㄱ', false), Some('\u{1100}'));
+        assert_eq!(to_conjoining_jamo('ㄱ', true), Some('\u{11A8}'));
+        assert_eq!(to_conjoining_jamo('ㄳ', false), None);
+        assert_eq!(to_conjoining_jamo('ㄸ', true), None);
+        assert_eq!(to_conjoining_jamo('ㅏ', true), Some('\u{1161}'));
+    }
+
+    /// The decomposition of a syllable, as specified by Unicode (section 3.12).
+    fn unicode_decomposition(code: u32) -> String {
+        let index = code - 0xAC00;
+        let mut s = String::new();
+        s.push(char::from_u32(0x1100 + index / (21 * 28)).unwrap());
+        s.push(char::from_u32(0x1161 + (index % (21 * 28)) / 28).unwrap());
+        let fin = index % 28;
+        if fin > 0 {
+            s.push(char::from_u32(0x11A7 + fin).unwrap());
+        }
+        s
+    }
+
+    #[test]
+    fn normalization_of_every_syllable() {
+        for code in 0xAC00..0xD7A4 {
+            let syllable = char::from_u32(code).unwrap().to_string();
+            let decomposed = unicode_decomposition(code);
+            assert_eq!(decompose(&syllable), decomposed);
+            assert_eq!(compose(&decomposed), syllable);
+        }
+    }
+
+    #[test]
+    fn normalization_of_text() {
+        let nfc = "한글 파일.txt";
+        let nfd = "\u{1112}\u{1161}\u{11AB}\u{1100}\u{1173}\u{11AF} \u{1111}\u{1161}\u{110B}\u{1175}\u{11AF}.txt";
+        assert_eq!(decompose(nfc), nfd);
+        assert_eq!(compose(nfd), nfc);
+        // A syllable without a final takes a following final
+        assert_eq!(compose("가\u{11A8}"), "각");
+        // Lone jamo and syllables with a final are left as they are
+        assert_eq!(compose("\u{1100}\u{1100}\u{11A8}각\u{11A8}ㄱㅏ"), "\u{1100}\u{1100}\u{11A8}각\u{11A8}ㄱㅏ");
+        assert_eq!(decompose("ㄱ a"), "ㄱ a");
+    }
+
+    #[test]
+    fn halfwidth_jamo() {
+        let mut normalized = String::new();
+        for code in 0xFFA0..0xFFE0 {
+            let ch = char::from_u32(code).unwrap();
+            let jamo = normalize_jamo_variants(&ch.to_string());
+            if jamo != ch.to_string() {
+                normalized.push_str(&jamo);
+            }
+        }
+        let compatibility: String = (0x3131..0x3164).map(|code| char::from_u32(code).unwrap()).collect();
+        assert_eq!(normalized, compatibility);
+        assert_eq!(normalize_jamo_variants("\u{FFBE}\u{FFC2}\u{FFA4}"), "ㅎㅏㄴ");
+    }
+
+    #[test]
+    fn enclosed_hangeul() {
+        let letters = "ㄱㄴㄷㄹㅁㅂㅅㅇㅈㅊㅋㅌㅍㅎ";
+        let syllables = "가나다라마바사아자차카타파하";
+        let mut expected: Vec<String> = Vec::new();
+        expected.extend(letters.chars().map(|ch| format!("({})", ch)));
+        expected.extend(syllables.chars().map(|ch| format!("({})", ch)));
+        expected.extend(vec!["(주)".to_string(), "(오전)".to_string(), "(오후)".to_string()]);
+        expected.extend(letters.chars().map(|ch| ch.to_string()));
+        expected.extend(syllables.chars().map(|ch| ch.to_string()));
+        expected.extend(vec!["참고".to_string(), "주의".to_string(), "우".to_string()]);
+        let codes = (0x3200..0x321F).chain(0x3260..0x327F);
+        let normalized: Vec<_> = codes
+            .map(|code| normalize_jamo_variants(&char::from_u32(code).unwrap().to_string()))
+            .collect();
+        assert_eq!(normalized, expected);
+        assert_eq!(normalize_jamo_variants("\u{321F}\u{327F}"), "\u{321F}\u{327F}");
+    }
+
+    #[test]
+    fn extended_jamo() {
+        let mapped = [
+            (0xA964, 'ㄺ'), (0xA966, 'ㅪ'), (0xA968, 'ㄻ'), (0xA969, 'ㄼ'), (0xA96C, 'ㄽ'), (0xA971, 'ㅯ'),
+            (0xD7CD, 'ㄸ'), (0xD7E3, 'ㅳ'), (0xD7E6, 'ㅃ'), (0xD7E7, 'ㅵ'), (0xD7E8, 'ㅶ'), (0xD7EF, 'ㅾ'),
+            (0xD7F9, 'ㅉ'),
+        ];
+        let codes = (0xA960..0xA97D).chain(0xD7B0..0xD7C7).chain(0xD7CB..0xD7FC);
+        for code in codes {
+            let jamo = char::from_u32(code).unwrap().to_string();
+            let expected = match mapped.iter().find(|&&(mapped, _)| mapped == code) {
+                Some(&(_, compat)) => compat.to_string(),
+                None => jamo.clone(),
+            };
+            assert_eq!(normalize_jamo_variants(&jamo), expected, "{:X}", code);
+        }
+        assert_eq!(normalize_jamo_variants("\u{A964}\u{D7B0}"), "ㄺ\u{D7B0}");
+    }
+
+    #[test]
+    fn classification() {
+        use super::HangulKind::*;
+        let examples = [
+            ('한', Syllable),
+            ('\u{AC00}', Syllable),
+            ('\u{D7A3}', Syllable),
+            ('ㄱ', CompatibilityConsonant),
+            ('ㅄ', CompatibilityConsonant),
+            ('ㅿ', CompatibilityConsonant),
+            ('ㅏ', CompatibilityVowel),
+            ('ㆍ', CompatibilityVowel),
+            ('\u{1100}', ConjoiningInitial),
+            ('\u{A960}', ConjoiningInitial),
+            ('\u{1161}', ConjoiningVowel),
+            ('\u{D7B0}', ConjoiningVowel),
+            ('\u{11A8}', ConjoiningFinal),
+            ('\u{D7FB}', ConjoiningFinal),
+            ('\u{FFA1}', Halfwidth),
+            ('\u{FFDC}', Halfwidth),
+            ('a', Other),
+            ('漢', Other),
+            ('\u{3164}', Other),
+            ('\u{D7A4}', Other),
+            ('\u{FFC8}', Other),
+            ('㉠', Other),
+        ];
+        for &(ch, kind) in &examples {
+            assert_eq!(classify(ch), kind, "{:X}", ch as u32);
+        }
+        for code in 0xAC00..0xD7A4 {
+            assert!(is_syllable(char::from_u32(code).unwrap()));
+        }
+        for code in 0x3131..0x3164 {
+            assert!(is_jamo(char::from_u32(code).unwrap()));
+        }
+        assert!(is_hangeul('한') && is_hangeul('ㄱ') && !is_hangeul('k'));
+        assert!(!is_jamo('한') && !is_syllable('ㄱ'));
+    }
+
+    #[test]
+    fn compound_vowels() {
+        let mut compounds = 0;
+        for offset in 0..21 {
+            let vowel = Vowel::from_offset(offset).unwrap();
+            match vowel.split() {
+                (first, Some(second)) => {
+                    compounds += 1;
+                    assert_eq!(Vowel::join(first, second), Some(vowel));
+                    assert_eq!(first.split(), (first, None));
+                    assert_eq!(second.split(), (second, None));
+                }
+                (single, None) => assert_eq!(single, vowel),
+            }
+        }
+        assert_eq!(compounds, 7);
+        assert_eq!(Vowel::join(Vowel::O, Vowel::A), Some(Vowel::Wa));
+        assert_eq!(Vowel::join(Vowel::A, Vowel::O), None);
+        assert_eq!(Vowel::join(Vowel::U, Vowel::A), None);
+    }
+
+    #[test]
+    fn compound_finals() {
+        let mut compounds = 0;
+        for offset in 1..28 {
+            let fin = Final::from_offset(offset).unwrap();
+            match fin.split() {
+                (first, Some(second)) => {
+                    compounds += 1;
+                    assert_eq!(Final::join(first, second), Some(fin));
+                    assert_eq!(first.split(), (first, None));
+                }
+                (single, None) => assert_eq!(single, fin),
+            }
+        }
+        assert_eq!(compounds, 11);
+        for offset in 0..19 {
+            let ini = Initial::from_offset(offset).unwrap();
+            let fin = Final::join(Final::Empty, ini);
+            assert_eq!(fin.map(|fin| fin.as_char()), fin.map(|_| ini.as_char()));
+        }
+        assert_eq!(Final::join(Final::Empty, Initial::Dd), None);
+        assert_eq!(Final::join(Final::L, Initial::G), Some(Final::Lg));
+        assert_eq!(Final::join(Final::G, Initial::G), None);
+        assert_eq!(Final::join(Final::Lg, Initial::S), None);
+    }
+
+    #[test]
+    fn initials_as_finals() {
+        for offset in 0..19 {
+            let ini = Initial::from_offset(offset).unwrap();
+            match ini.as_final() {
+                Some(fin) => {
+                    assert_eq!(fin.as_char(), ini.as_char());
+                    assert_eq!(fin.as_initial(), Some(ini));
+                    assert_eq!(Final::try_from(ini), Ok(fin));
+                    assert_eq!(Initial::try_from(fin), Ok(ini));
+                }
+                None => {
+                    assert!([Initial::Dd, Initial::Bb, Initial::Jj].contains(&ini));
+                    assert_eq!(Final::try_from(ini), Err(NoSuchJamo));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn finals_as_initials() {
+        let mut singles = 0;
+        for offset in 0..28 {
+            let fin = Final::from_offset(offset).unwrap();
+            match fin.as_initial() {
+                Some(ini) => {
+                    singles += 1;
+                    assert_eq!(ini.as_final(), Some(fin));
+                }
+                None => {
+                    assert!(fin == Final::Empty || fin.split().1.is_some(), "{:?}", fin);
+                    assert_eq!(Initial::try_from(fin), Err(NoSuchJamo));
+                }
+            }
+        }
+        assert_eq!(singles, 16);
+        assert_eq!(Final::L.as_initial(), Some(Initial::R));
+    }
+}
@@ -0,0 +1,128 @@
+//! Detection of romaja that could have been meant differently.
+//!
+//! Romaja is read greedily, so a final consonant moves on to a following
+//! vowel (`masyeo` => `마셔`, not `맛여`) and letters that make up a longer
+//! vowel are read together (`eo` => `어`, not `에오`). Writing the separator
+//! of the scheme (`x` for the romaja of this library) between the letters
+//! gives the other reading, so every place where that would change the
+//! result is reported.
+
+use hangeul2::Initial;
+
+use super::{read_jamo, deromanize_strict_with, ConversionScheme, InputScheme};
+
+/// The kinds of ambiguity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// A final consonant that moved on to the following vowel, as in
+    /// `masyeo` (`마셔`, or `맛여`).
+    MovedFinal,
+    /// Letters that were read as a single vowel, as in `eo` (`어`, or
+    /// `에오`).
+    MergedVowel,
+}
+
+/// Another way that the text could have been meant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ambiguity {
+    pub kind: Kind,
+    /// The byte offset where a separator changes the result.
+    pub position: usize,
+    /// The text with the separator written at the position.
+    pub romaja: String,
+    /// The 한글 that the changed text is converted to.
+    pub hangeul: String,
+}
+
+/// Finds every place in the given romaja where a separator (`x`) between two
+/// letters would give a different, complete conversion, in the order of the
+/// text.
+///
+/// Ex: `masyeo` (`마셔`) => `masxyeo` (`맛여`)
+pub fn ambiguities(text: &str) -> Vec<Ambiguity> {
+    ambiguities_with(text, InputScheme::Dero)
+}
+
+/// Finds every place in the given text, read as the given scheme, where the
+/// separator of the scheme would give a different, complete conversion.
+/// See `ambiguities`.
+pub fn ambiguities_with<S: ConversionScheme>(text: &str, scheme: S) -> Vec<Ambiguity> {
+    let mut original = String::new();
+    let mut starts = Vec::new();
+    read_jamo(text, &scheme, |i| starts.push(i), |ch, _| original.push(ch));
+    let separator = scheme.separator();
+    let mut found: Vec<Ambiguity> = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, ch)) = chars.next() {
+        let (position, next) = match chars.peek() {
+            Some(&(position, next)) => (position, next),
+            None => break,
+        };
+        if !ch.is_ascii_alphabetic() || !next.is_ascii_alphabetic() {
+            continue;
+        }
+        // The separator must start a syllable of its own
+        let romaja = format!("{}{}{}", &text[..position], separator, &text[position..]);
+        let rem = &romaja[position..];
+        if scheme.read_initial(rem) != Some((Initial::Ieung, separator.len()))
+            || scheme.read_vowel(&text[position..]).is_none()
+        {
+            continue;
+        }
+        // Alternatives that leave lone jamo or letters are not meant
+        let hangeul = match deromanize_strict_with(&romaja, &scheme) {
+            Ok(hangeul) => hangeul,
+            Err(_) => continue,
+        };
+        if hangeul != original && found.iter().all(|other| other.hangeul != hangeul) {
+            let kind = if starts.contains(&position) { Kind::MovedFinal } else { Kind::MergedVowel };
+            found.push(Ambiguity { kind, position, romaja, hangeul });
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ambiguities, ambiguities_with, Ambiguity, Kind};
+    use super::super::InputScheme;
+
+    fn alternatives(text: &str) -> Vec<(Kind, String)> {
+        ambiguities(text).into_iter().map(|ambiguity| (ambiguity.kind, ambiguity.hangeul)).collect()
+    }
+
+    #[test]
+    fn moved_finals() {
+        assert_eq!(ambiguities("masyeo")[0], Ambiguity {
+            kind: Kind::MovedFinal,
+            position: 3,
+            romaja: "masxyeo".to_string(),
+            hangeul: "맛여".to_string(),
+        });
+        assert_eq!(alternatives("dalgi"), vec![(Kind::MovedFinal, "닭이".to_string())]);
+    }
+
+    #[test]
+    fn merged_vowels() {
+        assert_eq!(alternatives("eo"), vec![(Kind::MergedVowel, "에오".to_string())]);
+        assert_eq!(alternatives("gae"), vec![(Kind::MergedVowel, "가에".to_string())]);
+        assert_eq!(alternatives("masyeo")[1..], [
+            (Kind::MergedVowel, "마스어".to_string()),
+            (Kind::MergedVowel, "마셰오".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn unambiguous_text() {
+        assert!(ambiguities("hangyl").is_empty());
+        assert!(ambiguities("ga na").is_empty());
+        assert!(alternatives("masxyeo").iter().all(|&(kind, _)| kind == Kind::MergedVowel));
+    }
+
+    #[test]
+    fn other_schemes() {
+        let found = ambiguities_with("masyeo", InputScheme::Revised);
+        assert_eq!(found[0].romaja, "mas-yeo");
+        assert_eq!(found[0].hangeul, "맛여");
+    }
+}
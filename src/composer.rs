@@ -0,0 +1,201 @@
+//! Incremental conversion of romaja typed one key at a time, as needed by
+//! input methods.
+//!
+//! A `Composer` keeps the text that can no longer change (the committed
+//! text) apart from the word that is still being typed (the preedit). Only
+//! the preedit is converted again when a key is pressed, and it is committed
+//! whenever a whitespace character is typed.
+//!
+//! The whole preedit is converted for every key, since a key can still change
+//! syllables before the last one (`gac` => `가c`, but `gach` => `갗`), so the
+//! work done for a key grows with the length of the word being typed, though
+//! not with the length of the committed text.
+
+use super::{read_jamo, ConversionScheme, InputScheme};
+
+/// Converts romaja to 한글 one key at a time.
+///
+/// Ex: typing `h`, `a`, `n` gives the preedit `한`, and typing `a` after that
+/// changes it to `하나`.
+#[derive(Debug, Clone)]
+pub struct Composer<S = InputScheme> {
+    scheme: S,
+    committed: String,
+    input: String,
+    preedit: String,
+}
+
+impl Composer<InputScheme> {
+    /// Creates a composer that reads the romaja of this library.
+    pub fn dero() -> Composer<InputScheme> {
+        Composer::new(InputScheme::Dero)
+    }
+}
+
+impl<S: ConversionScheme> Composer<S> {
+    /// Creates a composer that reads romaja using the given scheme.
+    pub fn new(scheme: S) -> Composer<S> {
+        Composer {
+            scheme,
+            committed: String::new(),
+            input: String::new(),
+            preedit: String::new(),
+        }
+    }
+
+    /// The text that will no longer change.
+    pub fn committed(&self) -> &str {
+        &self.committed
+    }
+
+    /// The 한글 of the word that is still being typed.
+    pub fn preedit(&self) -> &str {
+        &self.preedit
+    }
+
+    /// The romaja of the word that is still being typed.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Returns whether nothing has been typed (or everything was taken).
+    pub fn is_empty(&self) -> bool {
+        self.committed.is_empty() && self.input.is_empty()
+    }
+
+    /// Types a single key. Whitespace commits the preedit.
+    pub fn push(&mut self, ch: char) {
+        if ch.is_whitespace() {
+            self.commit();
+            self.committed.push(ch);
+        } else {
+            self.input.push(ch);
+            self.update();
+        }
+    }
+
+    /// Commits the preedit, followed by the given text as is.
+    pub fn push_literal(&mut self, text: &str) {
+        self.commit();
+        self.committed.push_str(text);
+    }
+
+    /// Removes the last jamo of the preedit, or the last committed character
+    /// if there is no preedit. Returns the romaja (or character) that was
+    /// removed.
+    ///
+    /// Ex: backspace after `chae` (`채`) removes `ae`, leaving `ㅊ`.
+    pub fn backspace(&mut self) -> Option<String> {
+        if self.input.is_empty() {
+            return self.committed.pop().map(|ch| ch.to_string());
+        }
+        let mut last = 0;
+        read_jamo(&self.input, &self.scheme, |i| last = i, |_, _| {});
+        let removed = self.input.split_off(last);
+        self.update();
+        Some(removed)
+    }
+
+    /// Commits the preedit.
+    pub fn commit(&mut self) {
+        self.committed.push_str(&self.preedit);
+        self.input.clear();
+        self.preedit.clear();
+    }
+
+    /// Commits the preedit and returns all of the committed text, leaving
+    /// the composer empty.
+    pub fn take(&mut self) -> String {
+        self.commit();
+        ::std::mem::take(&mut self.committed)
+    }
+
+    /// Discards everything that has been typed.
+    pub fn clear(&mut self) {
+        self.committed.clear();
+        self.input.clear();
+        self.preedit.clear();
+    }
+
+    fn update(&mut self) {
+        let preedit = &mut self.preedit;
+        preedit.clear();
+        read_jamo(&self.input, &self.scheme, |_| {}, |ch, _| preedit.push(ch));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Composer;
+    use super::super::{deromanize, InputScheme};
+
+    fn type_text<S: ::ConversionScheme>(composer: &mut Composer<S>, text: &str) {
+        for ch in text.chars() {
+            composer.push(ch);
+        }
+    }
+
+    #[test]
+    fn preedit_follows_typing() {
+        let mut composer = Composer::dero();
+        let mut shown = Vec::new();
+        for ch in "hana".chars() {
+            composer.push(ch);
+            shown.push(composer.preedit().to_string());
+        }
+        assert_eq!(shown, vec!["ㅎ", "하", "한", "하나"]);
+        assert_eq!(composer.committed(), "");
+        assert_eq!(composer.input(), "hana");
+    }
+
+    #[test]
+    fn whitespace_commits() {
+        let mut composer = Composer::dero();
+        type_text(&mut composer, "annyeoxhaseyo jeo");
+        assert_eq!(composer.committed(), "안녕하세요 ");
+        assert_eq!(composer.preedit(), "저");
+        assert_eq!(composer.take(), "안녕하세요 저");
+        assert!(composer.is_empty());
+    }
+
+    #[test]
+    fn matches_deromanize() {
+        let text = "igeo mweoyeyo? [x] dalgi masxiSxeoyo";
+        let mut composer = Composer::dero();
+        type_text(&mut composer, text);
+        assert_eq!(composer.take(), deromanize(text));
+    }
+
+    #[test]
+    fn backspace_removes_jamo() {
+        let mut composer = Composer::dero();
+        type_text(&mut composer, "chaeg");
+        assert_eq!(composer.preedit(), "책");
+        assert_eq!(composer.backspace().as_ref().map(|s| &s[..]), Some("g"));
+        assert_eq!(composer.preedit(), "채");
+        assert_eq!(composer.backspace().as_ref().map(|s| &s[..]), Some("ae"));
+        assert_eq!(composer.preedit(), "ㅊ");
+        assert_eq!(composer.backspace().as_ref().map(|s| &s[..]), Some("ch"));
+        assert_eq!(composer.preedit(), "");
+        assert_eq!(composer.backspace(), None);
+    }
+
+    #[test]
+    fn backspace_into_committed_text() {
+        let mut composer = Composer::dero();
+        type_text(&mut composer, "dalg ");
+        composer.push_literal("!");
+        assert_eq!(composer.backspace().as_ref().map(|s| &s[..]), Some("!"));
+        assert_eq!(composer.backspace().as_ref().map(|s| &s[..]), Some(" "));
+        assert_eq!(composer.committed(), "닭");
+    }
+
+    #[test]
+    fn other_schemes() {
+        let mut composer = Composer::new(InputScheme::Revised);
+        type_text(&mut composer, "hang");
+        assert_eq!(composer.preedit(), "항");
+        type_text(&mut composer, "eul");
+        assert_eq!(composer.preedit(), "한글");
+    }
+}
@@ -0,0 +1,252 @@
+//! Text that is left untouched by the conversion.
+//!
+//! Escaped text is written between an opening and a closing delimiter
+//! (`[` and `]` by default). Delimiters can be nested, in which case only
+//! the outermost pair is removed, so `[[x]]` => `[x]`. A delimiter that
+//! is not closed escapes the rest of the text, and a closing delimiter
+//! outside of an escape is left as it is. An escape character can be set
+//! to make the character after it literal, so that `\[` => `[` with `\` as
+//! the escape character. There is none by default.
+//!
+//! The same delimiters can instead mark the only text that is converted,
+//! with `deromanize_marked`.
+
+use std::error::Error;
+use std::fmt;
+
+/// The delimiters and escape character of escaped text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EscapeConfig {
+    /// The sequence that starts escaped text.
+    pub open: String,
+    /// The sequence that ends escaped text.
+    pub close: String,
+    /// The character that makes the character after it literal.
+    pub escape: Option<char>,
+}
+
+/// An error from creating an `EscapeConfig` with an empty delimiter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EmptyDelimiter;
+
+impl fmt::Display for EmptyDelimiter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Escape delimiters must not be empty")
+    }
+}
+
+impl Error for EmptyDelimiter {}
+
+impl EscapeConfig {
+    /// Creates a config with the given delimiters, and no escape character.
+    /// When the delimiters are the same, escapes cannot be nested.
+    pub fn new(open: &str, close: &str) -> Result<EscapeConfig, EmptyDelimiter> {
+        if open.is_empty() || close.is_empty() {
+            return Err(EmptyDelimiter);
+        }
+        Ok(EscapeConfig {
+            open: open.to_string(),
+            close: close.to_string(),
+            escape: None,
+        })
+    }
+
+    /// The config of marked text, with `{{` and `}}` as delimiters.
+    pub fn marked() -> EscapeConfig {
+        EscapeConfig {
+            open: "{{".to_string(),
+            close: "}}".to_string(),
+            escape: None,
+        }
+    }
+
+    /// Returns the config with the given escape character (or none).
+    pub fn with_escape(mut self, escape: Option<char>) -> EscapeConfig {
+        self.escape = escape;
+        self
+    }
+}
+
+impl Default for EscapeConfig {
+    fn default() -> EscapeConfig {
+        EscapeConfig {
+            open: "[".to_string(),
+            close: "]".to_string(),
+            escape: None,
+        }
+    }
+}
+
+/// What a character of the text turned out to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Token {
+    /// A character outside of the delimiters.
+    Outside(char),
+    /// A character between the delimiters (including nested delimiters).
+    Inside(char),
+    /// A character made literal by the escape character.
+    Escaped(char),
+    /// The start or end of delimited text, which separates the text around it.
+    Delimiter,
+}
+
+/// Splits text into delimited and other text one character at a time.
+///
+/// Characters that could be the start of a delimiter are held back until
+/// it is known whether they are, so `finish` must be called at the end of
+/// the text.
+#[derive(Debug, Clone)]
+pub struct Escaper<'a> {
+    config: &'a EscapeConfig,
+    depth: usize,
+    escaping: bool,
+    pending: String,
+}
+
+impl<'a> Escaper<'a> {
+    pub fn new(config: &'a EscapeConfig) -> Escaper<'a> {
+        Escaper { config, depth: 0, escaping: false, pending: String::new() }
+    }
+
+    /// Returns whether the next character would be outside of the delimiters,
+    /// unless it starts one.
+    pub fn is_idle(&self) -> bool {
+        self.depth == 0 && !self.escaping && self.pending.is_empty()
+    }
+
+    /// The characters that are held back, as they might start a delimiter.
+    pub fn pending(&self) -> &str {
+        &self.pending
+    }
+
+    /// Reads the next character of the text.
+    pub fn push(&mut self, ch: char, tokens: &mut Vec<Token>) {
+        if self.escaping {
+            self.escaping = false;
+            tokens.push(Token::Escaped(ch));
+        } else if Some(ch) == self.config.escape {
+            self.resolve(true, tokens);
+            self.escaping = true;
+        } else {
+            self.pending.push(ch);
+            self.resolve(false, tokens);
+        }
+    }
+
+    /// Reads the end of the text.
+    pub fn finish(&mut self, tokens: &mut Vec<Token>) {
+        self.resolve(true, tokens);
+        if self.escaping {
+            self.escaping = false;
+            tokens.extend(self.config.escape.map(Token::Escaped));
+        }
+        self.depth = 0;
+    }
+
+    fn resolve(&mut self, finished: bool, tokens: &mut Vec<Token>) {
+        let open = &self.config.open[..];
+        let close = &self.config.close[..];
+        let nests = open != close;
+        while !self.pending.is_empty() {
+            if self.depth > 0 && self.pending == close {
+                self.depth -= 1;
+                if self.depth == 0 {
+                    tokens.push(Token::Delimiter);
+                } else {
+                    tokens.extend(close.chars().map(Token::Inside));
+                }
+                self.pending.clear();
+            } else if self.pending == open && (self.depth == 0 || nests) {
+                if self.depth == 0 {
+                    tokens.push(Token::Delimiter);
+                } else {
+                    tokens.extend(open.chars().map(Token::Inside));
+                }
+                self.depth += 1;
+                self.pending.clear();
+            } else if !finished && (open.starts_with(&self.pending[..])
+                || (self.depth > 0 && close.starts_with(&self.pending[..])))
+            {
+                return;
+            } else {
+                let ch = self.pending.remove(0);
+                tokens.push(if self.depth == 0 { Token::Outside(ch) } else { Token::Inside(ch) });
+            }
+        }
+    }
+}
+
+/// Splits the text into delimited and other text.
+pub fn tokens(text: &str, config: &EscapeConfig) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut escaper = Escaper::new(config);
+    for ch in text.chars() {
+        escaper.push(ch, &mut tokens);
+    }
+    escaper.finish(&mut tokens);
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EmptyDelimiter, EscapeConfig};
+    use super::super::{deromanize_escaped, deromanize_escaped_with_config, InputScheme};
+    use super::super::{deromanize_marked, deromanize_marked_with};
+
+    fn escaped(text: &str, config: &EscapeConfig) -> String {
+        deromanize_escaped_with_config(text, InputScheme::Dero, config)
+    }
+
+    #[test]
+    fn default_delimiters() {
+        assert_eq!(deromanize_escaped("annyeox ha[se]yo"), "안녕 하se요");
+        assert_eq!(deromanize_escaped("annyeoxhaseyo, [Jakob]Si!"), "안녕하세요, Jakob씨!");
+        assert_eq!(deromanize_escaped("gan[]a"), "간아");
+        assert_eq!(deromanize_escaped("a]b[c"), "아]ㅂc");
+    }
+
+    #[test]
+    fn nesting() {
+        assert_eq!(deromanize_escaped("[[nhon]]etyd"), "[nhon]에튿");
+        assert_eq!(deromanize_escaped("[a[b]c]da"), "a[b]c다");
+        assert_eq!(deromanize_escaped("[a[b"), "a[b");
+    }
+
+    #[test]
+    fn escape_character() {
+        let config = EscapeConfig::default().with_escape(Some('\\'));
+        assert_eq!(escaped("\\[ga\\]", &config), "[가]");
+        assert_eq!(escaped("[\\]]ga", &config), "]가");
+        assert_eq!(escaped("ga\\", &config), "가\\");
+        // There is no escape character by default
+        assert_eq!(deromanize_escaped("ga\\na"), "가\\나");
+        assert_eq!(deromanize_escaped("C:\\dir [x]"), "C:\\딜 x");
+    }
+
+    #[test]
+    fn custom_delimiters() {
+        let config = EscapeConfig::new("{{", "}}").unwrap();
+        assert_eq!(escaped("[hangyl] {{se}}yo", &config), "[한글] se요");
+        assert_eq!(escaped("{{a{{b}}}}ga {ga}", &config), "a{{b}}가 {가}");
+        let quotes = EscapeConfig::new("`", "`").unwrap();
+        assert_eq!(escaped("ha`se`yo `a`", &quotes), "하se요 a");
+        assert_eq!(EscapeConfig::new("", "}"), Err(EmptyDelimiter));
+        assert_eq!(EscapeConfig::new("{", ""), Err(EmptyDelimiter));
+    }
+
+    #[test]
+    fn marked_text() {
+        assert_eq!(deromanize_marked("My name is {{jeimsy}}."), "My name is 제임스.");
+        assert_eq!(deromanize_marked("{{gan}}{{a}} and"), "간아 and");
+        // An unclosed marker applies to the rest of the text
+        assert_eq!(deromanize_marked("and {{a}"), "and 아}");
+        let escaping = EscapeConfig::marked().with_escape(Some('\\'));
+        let text = "\\{{ga}} {{ga\\na}}";
+        assert_eq!(deromanize_marked_with(text, InputScheme::Dero, &escaping), "{{ga}} 가n아");
+        let config = EscapeConfig::new("`k:", "`").unwrap();
+        let text = "a `k:hangyl` word, and `code`";
+        assert_eq!(deromanize_marked_with(text, InputScheme::Dero, &config), "a 한글 word, and `code`");
+        let revised = deromanize_marked_with("`k:hangeul`", InputScheme::Revised, &config);
+        assert_eq!(revised, "한글");
+    }
+}
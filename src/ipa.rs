@@ -0,0 +1,218 @@
+//! Transcription of 한글 into the International Phonetic Alphabet.
+//!
+//! Each word is transcribed as it is pronounced (see `pronounce`), with its
+//! syllables separated by dots, and other characters are kept as they are.
+//!
+//! Ex: `한국어` => `han.ɡu.ɡʌ`
+
+use hangeul2::{Initial, Vowel, Final, Block};
+
+use super::pronounce::pronounce_blocks;
+use super::sound::{self, Piece};
+
+/// How much detail a transcription shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transcription {
+    /// A single symbol for each consonant and vowel.
+    Broad,
+    /// Also shows how the consonants change with their position: plain stops
+    /// are voiceless at the start of a word and unreleased at the end of a
+    /// syllable, ㄹ is a flap between vowels, ㅅ is palatal before ㅣ, and ㅎ
+    /// is voiced between voiced sounds.
+    Narrow,
+}
+
+/// The broad transcription of an initial.
+fn initial_ipa(ini: Initial) -> &'static str {
+    use hangeul2::Initial::*;
+    match ini {
+        G     => "ɡ",
+        Gg    => "k͈",
+        N     => "n",
+        D     => "d",
+        Dd    => "t͈",
+        R     => "l",
+        M     => "m",
+        B     => "b",
+        Bb    => "p͈",
+        S     => "s",
+        Ss    => "s͈",
+        Ieung => "",
+        J     => "dʑ",
+        Jj    => "tɕ͈",
+        Ch    => "tɕʰ",
+        K     => "kʰ",
+        T     => "tʰ",
+        P     => "pʰ",
+        H     => "h",
+    }
+}
+
+fn vowel_ipa(vow: Vowel) -> &'static str {
+    use hangeul2::Vowel::*;
+    match vow {
+        A   => "a",
+        Ae  => "ɛ",
+        Ya  => "ja",
+        Yae => "jɛ",
+        Eo  => "ʌ",
+        E   => "e",
+        Yeo => "jʌ",
+        Ye  => "je",
+        O   => "o",
+        Wa  => "wa",
+        Wae => "wɛ",
+        Oe  => "ø",
+        Yo  => "jo",
+        U   => "u",
+        Weo => "wʌ",
+        We  => "we",
+        Wi  => "ɥi",
+        Yu  => "ju",
+        Y   => "ɯ",
+        Yi  => "ɰi",
+        I   => "i",
+    }
+}
+
+/// The broad transcription of a final, which is one of the finals left by
+/// the pronunciation.
+fn final_ipa(fin: Final) -> &'static str {
+    use hangeul2::Final::*;
+    match sound::neutralize(fin) {
+        Empty => "",
+        G     => "k",
+        N     => "n",
+        D     => "t",
+        L     => "l",
+        M     => "m",
+        B     => "p",
+        Ieung => "ŋ",
+        _     => unreachable!("Final not neutralized"),
+    }
+}
+
+/// The narrow transcription of an initial, after the given final of the
+/// syllable before it (or none at the start of a word).
+fn narrow_initial_ipa(ini: Initial, vow: Vowel, before: Option<Final>) -> &'static str {
+    use hangeul2::Initial::*;
+    // Plain stops after other stops are tense, so only voiced sounds are left
+    let voiced = before.is_some();
+    match ini {
+        G if !voiced => "k",
+        D if !voiced => "t",
+        B if !voiced => "p",
+        J if !voiced => "tɕ",
+        R if before == Some(Final::L) => "l",
+        R => "ɾ",
+        S if vow == Vowel::I => "ɕ",
+        Ss if vow == Vowel::I => "ɕ͈",
+        H if voiced => "ɦ",
+        _ => initial_ipa(ini),
+    }
+}
+
+fn narrow_final_ipa(fin: Final) -> &'static str {
+    match sound::neutralize(fin) {
+        Final::G => "k̚",
+        Final::D => "t̚",
+        Final::B => "p̚",
+        fin => final_ipa(fin),
+    }
+}
+
+/// Adds the transcription of a word, as it is pronounced.
+fn transcribe_word(word: &[Block], transcription: Transcription, s: &mut String) {
+    let mut before = None;
+    for (i, block) in pronounce_blocks(word).into_iter().enumerate() {
+        if i > 0 {
+            s.push('.');
+        }
+        let (ini, fin) = match transcription {
+            Transcription::Broad => (initial_ipa(block.initial), final_ipa(block.fin)),
+            Transcription::Narrow => {
+                (narrow_initial_ipa(block.initial, block.vowel, before), narrow_final_ipa(block.fin))
+            }
+        };
+        let vowel = vowel_ipa(block.vowel);
+        s.push_str(ini);
+        // ㅈ ㅉ ㅊ are palatal already, so 져 is pronounced as 저
+        match block.initial {
+            Initial::J | Initial::Jj | Initial::Ch => s.push_str(vowel.trim_start_matches('j')),
+            _ => s.push_str(vowel),
+        }
+        s.push_str(fin);
+        before = Some(block.fin);
+    }
+}
+
+/// Transcribes the 한글 of the given text into IPA, using a broad
+/// transcription.
+///
+/// Ex: `한국어` => `han.ɡu.ɡʌ`
+pub fn ipa(text: &str) -> String {
+    ipa_with(text, Transcription::Broad)
+}
+
+/// Transcribes the 한글 of the given text into IPA, with the given detail.
+/// See `ipa`.
+///
+/// Ex: `ipa_with("학교", Transcription::Narrow)` => `hak̚.k͈jo`
+pub fn ipa_with(text: &str, transcription: Transcription) -> String {
+    let mut s = String::new();
+    for piece in sound::pieces(text) {
+        match piece {
+            Piece::Word(word) => transcribe_word(&word, transcription, &mut s),
+            Piece::Other(ch) => s.push(ch),
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ipa, ipa_with, Transcription};
+
+    fn narrow(text: &str) -> String {
+        ipa_with(text, Transcription::Narrow)
+    }
+
+    #[test]
+    fn broad() {
+        let examples = [
+            ("한국어", "han.ɡu.ɡʌ"),
+            ("국물", "ɡuŋ.mul"),
+            ("같이", "ɡa.tɕʰi"),
+            ("학교", "hak.k͈jo"),
+            ("사랑", "sa.laŋ"),
+            ("가져", "ɡa.dʑʌ"),
+            ("의사", "ɰi.sa"),
+        ];
+        for &(word, expected) in &examples {
+            assert_eq!(ipa(word), expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn narrow_allophones() {
+        let examples = [
+            ("한국어", "han.ɡu.ɡʌ"),
+            ("가방", "ka.baŋ"),
+            ("학교", "hak̚.k͈jo"),
+            ("사랑", "sa.ɾaŋ"),
+            ("칼날", "kʰal.lal"),
+            ("시씨", "ɕi.ɕ͈i"),
+            ("은행", "ɯn.ɦɛŋ"),
+            ("좋아", "tɕo.a"),
+        ];
+        for &(word, expected) in &examples {
+            assert_eq!(narrow(word), expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(ipa("한국어, 공부!"), "han.ɡu.ɡʌ, ɡoŋ.bu!");
+        assert_eq!(narrow("ㅋㅋ 잎"), "ㅋㅋ ip̚");
+    }
+}
@@ -22,6 +22,26 @@
 //! the 'deromanize_escaped' function.
 //! 
 //! Ex: `annyeox ha[se]yo` => `안녕 하se요`
+//!
+//! Brackets can be nested, in which case only the outermost pair is removed.
+//! Other delimiters, and an escape character that makes the next character
+//! literal, can be used with an `EscapeConfig`.
+//!
+//! Ex: `[[x]] x` => `[x] ㅇ`
+//!
+//! Text that is mostly written in other languages can instead mark the
+//! romaja to convert, using `deromanize_marked`.
+//!
+//! Ex: `My name is {{jeimsy}}.` => `My name is 제임스.`
+//!
+//! Markdown documents can be converted with `deromanize_markdown`, which only
+//! converts the prose, and leaves code, links, HTML and front matter as is.
+//!
+//! # Streams
+//!
+//! Large texts can be converted as they are read, by writing them to a
+//! `DeroWriter` or by reading them through `deromanize_reader`. Only the text
+//! since the last whitespace is kept in memory (see `stream`).
 //! 
 //! # Rules
 //! Aside from those conversions, the following rules hold:
@@ -39,17 +59,97 @@
 //! - Initials not followed by a vowel will be left as standalone characters
 //! 
 //!   Ex: `jinJa, kkkkkkk` => `진짜, ㅋㅋㅋㅋㅋㅋㅋ`
+//!
+//! # Romanization
+//! 한글 can be converted back into this format with the 'romanize' function,
+//! which inserts ieungs where they are needed for the text to deromanize into
+//! the same syllables.
+//!
+//! Ex: `맛있어요` => `masxiSxeoyo`
+//!
+//! The official Revised Romanization, the McCune–Reischauer romanization and
+//! the Yale romanization are available through the 'romanize_with' function.
+//!
+//! Ex: `신라` => `silla` (Revised), `sinla` (Yale)
+//!
+//! # Pronunciation
+//! The 'pronounce' function shows how 한글 text is pronounced, by applying the
+//! sound changes between the syllables of each word.
+//!
+//! Ex: `국물` => `궁물`, `같이` => `가치`
+//!
+//! The pronunciation can also be transcribed into IPA with 'ipa', or with
+//! 'ipa_with' and 'Transcription::Narrow' for the allophones of each sound.
+//!
+//! Ex: `한국어` => `han.ɡu.ɡʌ`
+//!
+//! # Revised Romanization input
+//! Text written in the Revised Romanization can be read with
+//! 'deromanize_with' and 'InputScheme::Revised'. As the scheme follows the
+//! pronunciation, the result is the pronounced form of the text. An `ng`
+//! followed by a vowel is read as ㄴ and ㄱ, and a hyphen marks a syllable
+//! boundary and is removed:
+//!
+//! Ex: `hangeul` => `한글`, `hang-eul` => `항을`, `jung-ang` => `중앙`
+//!
+//! # Input methods
+//!
+//! A `Composer` converts romaja one key at a time, keeping the word that is
+//! being typed apart from the committed text, and removes whole jamo on
+//! backspace.
+//!
+//! # Strict conversion
+//!
+//! `deromanize_strict` fails with a `DeroError` listing every part of the
+//! text that could not be converted, instead of passing it through.
+//!
+//! Ex: `kkk` => `ㅋ is not followed by a vowel` (3 times)
+//!
+//! `ambiguities` finds the places where an explicit ieung would give another
+//! reading of the romaja.
+//!
+//! Ex: `masyeo` => `마셔`, but `masxyeo` => `맛여`
 extern crate hangeul2;
 #[cfg(feature = "tui")]
 extern crate termion;
 
 use hangeul2::{Initial, Vowel, Final, Block};
 
+use std::ops::Range;
+
+mod sound;
+pub mod romanize;
+pub mod revised;
+pub mod mccune_reischauer;
+pub mod yale;
+pub mod table;
+pub mod composer;
+pub mod strict;
+pub mod ambiguity;
+pub mod escape;
+pub mod markdown;
+pub mod stream;
+pub mod pronounce;
+pub mod ipa;
+
+pub use romanize::{romanize, romanize_into, romanize_with, romanize_with_into};
+pub use table::TableScheme;
+pub use composer::Composer;
+pub use strict::{deromanize_strict, deromanize_strict_with, DeroError};
+pub use ambiguity::{ambiguities, ambiguities_with, Ambiguity, Kind as AmbiguityKind};
+pub use escape::EscapeConfig;
+pub use markdown::{deromanize_markdown, deromanize_markdown_with};
+pub use stream::{deromanize_reader, deromanize_reader_with, DeroReader, DeroWriter};
+pub use pronounce::{pronounce, pronounce_blocks};
+pub use ipa::{ipa, ipa_with, Transcription};
+
+use escape::Token;
+
 #[cfg(feature = "tui")]
 pub mod tui;
 
 #[cfg(feature = "tui")]
-pub use tui::start_interactive_loop;
+pub use tui::{start_interactive_loop, start_interactive_loop_with};
 
 
 #[derive(Debug)]
@@ -60,6 +160,116 @@ enum DeroState {
     AfterFinal(Initial, Vowel, Final),
 }
 
+/// The romaja schemes that can be read by `deromanize_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputScheme {
+    /// The romaja described in the crate documentation.
+    Dero,
+    /// The official Revised Romanization (see the 'revised' module).
+    Revised,
+}
+
+/// A romaja scheme read by the deromanization state machine.
+///
+/// Each method reads a single jamo from the start of the given text and
+/// returns it along with the number of bytes read. The provided methods read
+/// the romaja described in the crate documentation, so a scheme only needs to
+/// override the jamo that it writes differently.
+///
+/// The number of bytes read must be more than zero and end on a character
+/// boundary of the text. A jamo read with any other length is ignored, as if
+/// the scheme had not read anything.
+///
+/// Ex: A scheme where `q` is ㄲ:
+///
+/// ```
+/// # extern crate dero;
+/// # extern crate hangeul2;
+/// use dero::{ConversionScheme, deromanize_with, read_initial};
+/// use hangeul2::Initial;
+///
+/// struct Custom;
+///
+/// impl ConversionScheme for Custom {
+///     fn read_initial(&self, text: &str) -> Option<(Initial, usize)> {
+///         if text.starts_with('q') {
+///             Some((Initial::Gg, 1))
+///         } else {
+///             read_initial(text)
+///         }
+///     }
+/// }
+///
+/// assert_eq!(deromanize_with("qachi", Custom), "까치");
+/// ```
+pub trait ConversionScheme {
+    fn read_vowel(&self, text: &str) -> Option<(Vowel, usize)> {
+        read_vowel(text)
+    }
+
+    fn read_initial(&self, text: &str) -> Option<(Initial, usize)> {
+        read_initial(text)
+    }
+
+    fn read_final(&self, text: &str) -> Option<(Final, usize)> {
+        read_final(text)
+    }
+
+    /// The sequence that is read as an initial ㅇ, used to keep syllables
+    /// apart that would otherwise be read differently.
+    fn separator(&self) -> &str {
+        "x"
+    }
+}
+
+impl<S: ConversionScheme + ?Sized> ConversionScheme for &S {
+    fn read_vowel(&self, text: &str) -> Option<(Vowel, usize)> {
+        (**self).read_vowel(text)
+    }
+
+    fn read_initial(&self, text: &str) -> Option<(Initial, usize)> {
+        (**self).read_initial(text)
+    }
+
+    fn read_final(&self, text: &str) -> Option<(Final, usize)> {
+        (**self).read_final(text)
+    }
+
+    fn separator(&self) -> &str {
+        (**self).separator()
+    }
+}
+
+impl ConversionScheme for InputScheme {
+    fn read_vowel(&self, text: &str) -> Option<(Vowel, usize)> {
+        match *self {
+            InputScheme::Dero => read_vowel(text),
+            InputScheme::Revised => revised::read_vowel(text),
+        }
+    }
+
+    fn read_initial(&self, text: &str) -> Option<(Initial, usize)> {
+        match *self {
+            InputScheme::Dero => read_initial(text),
+            InputScheme::Revised => revised::read_initial(text),
+        }
+    }
+
+    fn read_final(&self, text: &str) -> Option<(Final, usize)> {
+        match *self {
+            InputScheme::Dero => read_final(text),
+            InputScheme::Revised => revised::read_final(text),
+        }
+    }
+
+    fn separator(&self) -> &str {
+        match *self {
+            InputScheme::Dero => "x",
+            InputScheme::Revised => "-",
+        }
+    }
+}
+
 /// Reads a vowel from the given romaja and the number of chars read.
 /// This also corresponds to bytes, as the characters must be ASCII chars.
 pub fn read_vowel(text: &str) -> Option<(Vowel, usize)> {
@@ -147,54 +357,24 @@ pub fn read_initial(text: &str) -> Option<(Initial, usize)> {
 /// Reads a final consonant combination from the given romaja and returns the 
 /// number of characters read.
 /// This also corresponds to bytes, as the characters must be ASCII chars.
-pub fn read_final(mut text: &str) -> Option<(Final, usize)> {
-    use hangeul2::Initial::*;
-    let (first, flen) = if let Some((ini, len)) = read_initial(text) {
-        text = &text[len..];
-        (ini, len)
-    } else {
-        return None;
-    };
-    let mut chars = text.chars();
-    let (fin, len) = match (first, chars.next()) {
-        (G, Some('s')) => (Final::Gs, 1),
-        (G, _) => (Final::G, 0),
-        (Gg, _) => (Final::Gg, 0),
-        (N, Some('j')) => (Final::Nj, 1),
-        (N, Some('h')) => (Final::Nh, 1),
-        (N, _) => (Final::N, 0),
-        (D, _) => (Final::D, 0),
-        (Dd, _) => return None,
-        (R, Some('g')) => (Final::Lg, 1),
-        (R, Some('m')) => (Final::Lm, 1),
-        (R, Some('b')) => (Final::Lb, 1),
-        (R, Some('s')) => (Final::Ls, 1),
-        (R, Some('t')) => (Final::Lt, 1),
-        (R, Some('p')) => (Final::Lp, 1),
-        (R, Some('h')) => (Final::Lh, 1),
-        (R, _) => (Final::L, 0),
-        (M, _) => (Final::M, 0),
-        (B, Some('s')) => (Final::Bs, 1),
-        (B, _) => (Final::B, 0),
-        (Bb, _) => return None,
-        (S, _) => (Final::S, 0),
-        (Ss, _) => (Final::Ss, 0),
-        (Ieung, _) => (Final::Ieung, 0),
-        (J, _) => (Final::J, 0),
-        (Jj, _) => return None,
-        (Ch, _) => (Final::Ch, 0),
-        (K, _) => (Final::K, 0),
-        (T, _) => (Final::T, 0),
-        (P, _) => (Final::P, 0),
-        (H, _) => (Final::H, 0),
-    };
-    Some((fin, flen + len))
+pub fn read_final(text: &str) -> Option<(Final, usize)> {
+    let (first, len) = read_initial(text)?;
+    let fin = first.as_final()?;
+    match read_initial(&text[len..]) {
+        Some((second, second_len)) => match Final::join(fin, second) {
+            Some(compound) => Some((compound, len + second_len)),
+            None => Some((fin, len)),
+        },
+        None => Some((fin, len)),
+    }
 }
 
-#[inline]
-fn skip<I>(iter: &mut I, n: usize) where I: Iterator {
-    for _ in 0..n {
-        iter.next();
+/// Splits a (non-empty) final into the part that stays in its syllable and
+/// the consonant that moves on to the next syllable when a vowel follows.
+fn split_final(fin: Final) -> (Final, Initial) {
+    match fin.split() {
+        (rest, Some(last)) => (rest, last),
+        (fin, None) => (Final::Empty, fin.as_initial().expect("Empty final split")),
     }
 }
 
@@ -207,130 +387,223 @@ pub fn deromanize(text: &str) -> String {
 
 /// Converts as much of the given romaja-containing string to 한글 as possible.
 pub fn deromanize_into(text: &str, s: &mut String) {
+    deromanize_with_into(text, InputScheme::Dero, s);
+}
+
+/// Converts as much of the given string to 한글 as possible, reading it as
+/// the given romaja scheme.
+///
+/// Ex: `deromanize_with("hangeul", InputScheme::Revised)` => `한글`
+pub fn deromanize_with<S: ConversionScheme>(text: &str, scheme: S) -> String {
+    let mut s = String::new();
+    deromanize_with_into(text, scheme, &mut s);
+    s
+}
+
+/// Converts as much of the given string to 한글 as possible, reading it as
+/// the given romaja scheme.
+pub fn deromanize_with_into<S: ConversionScheme>(text: &str, scheme: S, s: &mut String) {
+    read_jamo(text, scheme, |_| {}, |ch, _| s.push(ch));
+}
+
+/// Converts as much of the given string to 한글 as possible, reading it as
+/// the given romaja scheme, and returns every character produced together
+/// with the range of bytes in the text that it was read from.
+///
+/// A consonant that is moved to the next syllable belongs to that syllable,
+/// also when it is split from a compound final, so the ranges never overlap.
+///
+/// Ex: `dalgi` => `[('달', 0..3), ('기', 3..5)]`
+pub fn deromanize_with_spans<S: ConversionScheme>(text: &str, scheme: S) -> Vec<(char, Range<usize>)> {
+    let mut spans = Vec::new();
+    read_jamo(text, scheme, |_| {}, |ch, span| spans.push((ch, span)));
+    spans
+}
+
+/// Ignores a jamo read by a scheme if it was read from no bytes, or from bytes
+/// that do not end on a character boundary of the text.
+fn checked<T>(text: &str, read: Option<(T, usize)>) -> Option<(T, usize)> {
+    read.filter(|&(_, len)| len > 0 && text.is_char_boundary(len))
+}
+
+/// Returns where the consonant that is moved out of a compound final was read
+/// from, given the bytes that the final was read from. The whole final is
+/// kept with its syllable if the consonant cannot be found.
+fn moved_consonant_start<S: ConversionScheme>(text: &str, fin: Range<usize>, moved: Initial, scheme: &S) -> usize {
+    (fin.start + 1..fin.end)
+        .filter(|&b| text.is_char_boundary(b))
+        .find(|&b| {
+            let rest = &text[b..fin.end];
+            checked(rest, scheme.read_initial(rest)) == Some((moved, rest.len()))
+        })
+        .unwrap_or(fin.end)
+}
+
+/// Runs the deromanization state machine over the text, calling `on_read`
+/// with the byte offset of every jamo (or other character) that is read, and
+/// `emit` with every character produced and the bytes it was read from.
+fn read_jamo<S, F, E>(text: &str, scheme: S, mut on_read: F, mut emit: E)
+    where S: ConversionScheme, F: FnMut(usize), E: FnMut(char, Range<usize>)
+{
     use self::DeroState::*;
     let mut state = Empty;
-    let mut chars = text.char_indices().peekable();
-    while let Some(&(i, ch)) = chars.peek() {
+    let mut i = 0;
+    // Where the current syllable and its final consonant were read from
+    let mut start = 0;
+    let mut fin_start = 0;
+    while let Some(ch) = text[i..].chars().next() {
         let rem = &text[i..];
         //println!("State: {:?}", state);
         state = match state {
             Empty => {
-                if let Some((ini, len)) = read_initial(rem) {
-                    skip(&mut chars, len);
+                start = i;
+                if let Some((ini, len)) = checked(rem, scheme.read_initial(rem)) {
+                    on_read(i);
+                    i += len;
                     AfterInitial(ini)
-                } else if let Some((vow, len)) = read_vowel(rem) {
-                    skip(&mut chars, len);
+                } else if let Some((vow, len)) = checked(rem, scheme.read_vowel(rem)) {
+                    on_read(i);
+                    i += len;
                     AfterVowel(Initial::Ieung, vow)
                 } else {
-                    s.push(ch);
-                    chars.next();
+                    emit(ch, i..i + ch.len_utf8());
+                    on_read(i);
+                    i += ch.len_utf8();
                     Empty
                 }
             }
             AfterInitial(ini) => {
-                if let Some((nvow, len)) = read_vowel(rem) {
-                    skip(&mut chars, len);
+                if let Some((nvow, len)) = checked(rem, scheme.read_vowel(rem)) {
+                    on_read(i);
+                    i += len;
                     AfterVowel(ini, nvow)
                 } else {
-                    s.push(ini.as_char());
+                    emit(ini.as_char(), start..i);
                     Empty
                 }
             }
             AfterVowel(ini, vow) => {
-                if let Some((nfin, len)) = read_final(rem) {
-                    skip(&mut chars, len);
+                if let Some((nfin, len)) = checked(rem, scheme.read_final(rem)) {
+                    on_read(i);
+                    fin_start = i;
+                    i += len;
                     AfterFinal(ini, vow, nfin)
-                } else if let Some((nvow, len)) = read_vowel(rem) {
-                    skip(&mut chars, len);
-                    s.push(Block::from_parts(ini, vow, Final::Empty).combine());
+                } else if let Some((nvow, len)) = checked(rem, scheme.read_vowel(rem)) {
+                    on_read(i);
+                    emit(Block::from_parts(ini, vow, Final::Empty).combine(), start..i);
+                    start = i;
+                    i += len;
                     AfterVowel(Initial::Ieung, nvow)
                 // Consonants invalid in final position, ie: Bb
-                } else if let Some((nini, len)) = read_initial(rem) {
-                    skip(&mut chars, len);
-                    s.push(Block::from_parts(ini, vow, Final::Empty).combine());
+                } else if let Some((nini, len)) = checked(rem, scheme.read_initial(rem)) {
+                    on_read(i);
+                    emit(Block::from_parts(ini, vow, Final::Empty).combine(), start..i);
+                    start = i;
+                    i += len;
                     AfterInitial(nini)
                 } else {
-                    s.push(Block::from_parts(ini, vow, Final::Empty).combine());
-                    s.push(ch);
-                    chars.next();
+                    emit(Block::from_parts(ini, vow, Final::Empty).combine(), start..i);
+                    emit(ch, i..i + ch.len_utf8());
+                    on_read(i);
+                    i += ch.len_utf8();
                     Empty
                 }
             }
             AfterFinal(ini, vow, fin) => {
-                if let Some((nvow, len)) = read_vowel(rem) {
-                    use hangeul2::Final::*;
-                    skip(&mut chars, len);
-                    let (fin, nini) = match fin {
-                        G => (Empty, Initial::G),
-                        Gg => (Empty, Initial::Gg),
-                        Gs => (G, Initial::S),
-                        N => (Empty, Initial::N),
-                        Nj => (N, Initial::J),
-                        Nh => (N, Initial::H),
-                        D => (Empty, Initial::D),
-                        L => (Empty, Initial::R),
-                        Lg => (L, Initial::G),
-                        Lm => (L, Initial::M),
-                        Lb => (L, Initial::B),
-                        Ls => (L, Initial::S),
-                        Lt => (L, Initial::T),
-                        Lp => (L, Initial::P),
-                        Lh => (L, Initial::H),
-                        M => (Empty, Initial::M),
-                        B => (Empty, Initial::B),
-                        Bs => (B, Initial::S),
-                        S => (Empty, Initial::S),
-                        Ss => (Empty, Initial::Ss),
-                        Ieung => (Empty, Initial::Ieung),
-                        J => (Empty, Initial::J),
-                        Ch => (Empty, Initial::Ch),
-                        K => (Empty, Initial::K),
-                        T => (Empty, Initial::T),
-                        P => (Empty, Initial::P),
-                        H => (Empty, Initial::H),
-                        Empty => unreachable!(),
+                if let Some((nvow, len)) = checked(rem, scheme.read_vowel(rem)) {
+                    on_read(i);
+                    // The moved consonant starts the next syllable
+                    let (fin, nini) = split_final(fin);
+                    let moved_start = if fin == Final::Empty {
+                        fin_start
+                    } else {
+                        moved_consonant_start(text, fin_start..i, nini, &scheme)
                     };
-                    s.push(Block::from_parts(ini, vow, fin).combine());
+                    emit(Block::from_parts(ini, vow, fin).combine(), start..moved_start);
+                    start = moved_start;
+                    i += len;
                     AfterVowel(nini, nvow)
                 } else {
-                    s.push(Block::from_parts(ini, vow, fin).combine());
+                    emit(Block::from_parts(ini, vow, fin).combine(), start..i);
                     Empty
                 }
             }
         }
     }
+    let end = text.len();
     match state {
         Empty => {}
-        AfterInitial(ini) => s.push(ini.as_char()),
-        AfterVowel(ini, vow) => s.push(Block::from_parts(ini, vow, Final::Empty).combine()),
-        AfterFinal(ini, vow, fin) => s.push(Block::from_parts(ini, vow, fin).combine()),
+        AfterInitial(ini) => emit(ini.as_char(), start..end),
+        AfterVowel(ini, vow) => emit(Block::from_parts(ini, vow, Final::Empty).combine(), start..end),
+        AfterFinal(ini, vow, fin) => emit(Block::from_parts(ini, vow, fin).combine(), start..end),
     }
 }
 
 /// Converts as much of the given romaja-containing string to 한글 as possible.
-/// The conversion ignores all text between square brackets.
+/// The conversion ignores all text between square brackets (see `escape`).
 pub fn deromanize_escaped(text: &str) -> String {
-    const ESCAPE_START: char = '[';
-    const ESCAPE_END: char = ']';
+    deromanize_escaped_with(text, InputScheme::Dero)
+}
+
+/// Converts as much of the given romaja-containing string to 한글 as possible
+/// using the given scheme. See `deromanize_escaped`.
+pub fn deromanize_escaped_with<S: ConversionScheme>(text: &str, scheme: S) -> String {
+    deromanize_escaped_with_config(text, scheme, &EscapeConfig::default())
+}
+
+/// Converts as much of the given romaja-containing string to 한글 as possible
+/// using the given scheme, leaving the text escaped as described by the
+/// config untouched.
+///
+/// Ex: `{{se}}yo` => `se요` (with `{{` and `}}` as delimiters)
+pub fn deromanize_escaped_with_config<S>(text: &str, scheme: S, config: &EscapeConfig) -> String
+    where S: ConversionScheme
+{
+    deromanize_tokens(text, scheme, config, false)
+}
+
+/// Converts only the romaja between `{{` and `}}` in the given text to 한글,
+/// leaving everything else untouched.
+///
+/// Ex: `My name is {{jeimsy}}.` => `My name is 제임스.`
+pub fn deromanize_marked(text: &str) -> String {
+    deromanize_marked_with(text, InputScheme::Dero, &EscapeConfig::marked())
+}
+
+/// Converts only the romaja between the delimiters of the config to 한글
+/// using the given scheme, leaving everything else untouched.
+/// See `deromanize_marked`.
+///
+/// Ex: `` a `k:hangyl` word `` => `a 한글 word` (with `` `k: `` and `` ` ``
+/// as delimiters)
+pub fn deromanize_marked_with<S>(text: &str, scheme: S, config: &EscapeConfig) -> String
+    where S: ConversionScheme
+{
+    deromanize_tokens(text, scheme, config, true)
+}
+
+/// Converts the text either outside of or inside of the delimiters.
+fn deromanize_tokens<S>(text: &str, scheme: S, config: &EscapeConfig, inside: bool) -> String
+    where S: ConversionScheme
+{
     let mut s = String::new();
-    let mut i = 0;
-    while i < text.len() {
-        //println!("i: {}", i);
-        let rem = &text[i..];
-        if let Some(start) = rem.find(ESCAPE_START) {
-            deromanize_into(&rem[..start], &mut s);
-            if let Some(end) = rem.find(ESCAPE_END) {
-                s.push_str(&rem[start + ESCAPE_START.len_utf8() .. end]);
-                i += end + ESCAPE_END.len_utf8();
-            } else {
-                s.push_str(&rem[start + ESCAPE_START.len_utf8() ..]);
-                break;
+    let mut romaja = String::new();
+    for token in escape::tokens(text, config) {
+        match token {
+            Token::Outside(ch) if !inside => romaja.push(ch),
+            Token::Inside(ch) if inside => romaja.push(ch),
+            Token::Outside(ch) | Token::Inside(ch) | Token::Escaped(ch) => {
+                deromanize_with_into(&romaja, &scheme, &mut s);
+                romaja.clear();
+                s.push(ch);
+            }
+            Token::Delimiter => {
+                deromanize_with_into(&romaja, &scheme, &mut s);
+                romaja.clear();
             }
-        } else {
-            deromanize_into(rem, &mut s);
-            break;
         }
     }
+    deromanize_with_into(&romaja, &scheme, &mut s);
     s
 }
 
@@ -338,6 +611,7 @@ pub fn deromanize_escaped(text: &str) -> String {
 mod tests {
     use super::hangeul2::{Initial, Vowel, Final, Block};
     use super::{deromanize_escaped, deromanize, read_initial, read_vowel, read_final};
+    use super::{ConversionScheme, InputScheme, deromanize_with, deromanize_with_spans};
     #[test]
     fn test_everything() {
         println!("Hello Dero!");
@@ -388,4 +662,67 @@ mod tests {
         let escaped_garbage = "qdp:[rwufa]eonbcmev/[arp]dft[]sa[][][[nhon]]etydrnt";
         println!("Escaped garbage: {}", deromanize_escaped(escaped_garbage));
     }
-}
\ No newline at end of file
+
+    struct Team;
+
+    impl ConversionScheme for Team {
+        fn read_initial(&self, text: &str) -> Option<(Initial, usize)> {
+            if text.starts_with('q') {
+                Some((Initial::Gg, 1))
+            } else {
+                read_initial(text)
+            }
+        }
+
+        fn read_final(&self, text: &str) -> Option<(Final, usize)> {
+            if text.starts_with("ng") {
+                Some((Final::Ieung, 2))
+            } else {
+                read_final(text)
+            }
+        }
+    }
+
+    #[test]
+    fn custom_scheme() {
+        assert_eq!(deromanize_with("qachi", Team), "까치");
+        assert_eq!(deromanize_with("sarang", Team), "사랑");
+        assert_eq!(deromanize_with("annyeong haseyo", &Team), "안녕 하세요");
+        assert_eq!(deromanize_with("annyeox haseyo", InputScheme::Dero), "안녕 하세요");
+    }
+
+    struct Broken;
+
+    impl ConversionScheme for Broken {
+        fn read_initial(&self, text: &str) -> Option<(Initial, usize)> {
+            if text.starts_with('q') {
+                Some((Initial::Gg, 0))
+            } else if text.starts_with('é') {
+                Some((Initial::N, 1))
+            } else {
+                read_initial(text)
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_lengths_are_ignored() {
+        assert_eq!(deromanize_with("qa", Broken), "q아");
+        assert_eq!(deromanize_with("éa", Broken), "é아");
+    }
+
+    #[test]
+    fn spans() {
+        let spans = |text| deromanize_with_spans(text, InputScheme::Dero);
+        assert_eq!(spans("hangyl"), vec![('한', 0..3), ('글', 3..6)]);
+        assert_eq!(spans("chaeg, x"), vec![('책', 0..5), (',', 5..6), (' ', 6..7), ('ㅇ', 7..8)]);
+        // Moved and split final consonants
+        assert_eq!(spans("gagyn"), vec![('가', 0..2), ('근', 2..5)]);
+        assert_eq!(spans("dalgi"), vec![('달', 0..3), ('기', 3..5)]);
+        assert_eq!(spans("eobsa"), vec![('업', 0..3), ('사', 3..5)]);
+        assert_eq!(spans("baBa"), vec![('바', 0..2), ('빠', 2..4)]);
+        assert_eq!(spans("aa"), vec![('아', 0..1), ('아', 1..2)]);
+        let revised = deromanize_with_spans("han-geul", InputScheme::Revised);
+        assert_eq!(revised, vec![('한', 0..3), ('글', 3..8)]);
+    }
+}
@@ -2,11 +2,12 @@ extern crate dero;
 extern crate argonaut;
 
 use argonaut::{Parse, ArgDef};
+use dero::{Ambiguity, EscapeConfig, InputScheme, TableScheme, Transcription};
 
-use std::io::{self, Write};
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Read, Write};
 use std::process::{self, Command, Stdio};
 use std::env;
-use std::fs::OpenOptions;
+use std::fs::{File, OpenOptions};
 use std::path::Path;
 use std::error::Error;
 
@@ -70,9 +71,218 @@ fn look_up_word(text: &str) {
 #[cfg(not(target_os = "macos"))]
 fn look_up_word(_text: &str) {}
 
-fn convert_single(text: &str, copy: bool, look_up: bool, append_file: Option<String>, anki: bool) -> bool {
-    let output = dero::deromanize_escaped(text);
+/// The scheme that romaja is read with.
+enum Reader {
+    Builtin(InputScheme),
+    Table(TableScheme),
+}
+
+/// How text given on the command line is converted.
+struct Conversion {
+    reader: Reader,
+    escapes: EscapeConfig,
+    /// Whether only the text between the delimiters is converted.
+    marked: bool,
+    /// How the converted text is transcribed into IPA, if it is.
+    ipa: Option<Transcription>,
+}
+
+fn deromanize(text: &str, conversion: &Conversion) -> String {
+    let escapes = &conversion.escapes;
+    match &conversion.reader {
+        Reader::Builtin(scheme) if conversion.marked => dero::deromanize_marked_with(text, *scheme, escapes),
+        Reader::Builtin(scheme) => dero::deromanize_escaped_with_config(text, *scheme, escapes),
+        Reader::Table(scheme) if conversion.marked => dero::deromanize_marked_with(text, scheme, escapes),
+        Reader::Table(scheme) => dero::deromanize_escaped_with_config(text, scheme, escapes),
+    }
+}
+
+/// Deromanizes the text, and transcribes it into IPA if that was asked for.
+fn convert(text: &str, conversion: &Conversion) -> String {
+    let converted = deromanize(text, conversion);
+    match conversion.ipa {
+        Some(transcription) => dero::ipa_with(&converted, transcription),
+        None => converted,
+    }
+}
+
+/// Reads delimiters given as 'OPEN CLOSE'.
+fn parse_delimiters(value: &str) -> EscapeConfig {
+    let parts: Vec<_> = value.split_whitespace().collect();
+    if parts.len() != 2 {
+        eprintln!("Invalid delimiters '{}': expected 'OPEN CLOSE'", value);
+        process::exit(2);
+    }
+    match EscapeConfig::new(parts[0], parts[1]) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Invalid delimiters '{}': {}", value, err);
+            process::exit(2);
+        }
+    }
+}
+
+/// Reads an escape character, or 'none'.
+fn parse_escape_char(value: &str) -> Option<char> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Some(ch),
+        _ if value == "none" => None,
+        _ => {
+            eprintln!("Invalid escape character '{}'", value);
+            process::exit(2);
+        }
+    }
+}
+
+fn load_scheme(path: &str) -> Reader {
+    match TableScheme::from_file(path) {
+        Ok(scheme) => Reader::Table(scheme),
+        Err(err) => {
+            eprintln!("Could not load scheme '{}': {}", path, err);
+            process::exit(2);
+        }
+    }
+}
+
+/// Finds the final consonants that might have been meant to stay in their
+/// syllable.
+fn moved_finals(text: &str, conversion: &Conversion) -> Vec<Ambiguity> {
+    // Only plain romaja is checked
+    if conversion.marked || text.contains(&conversion.escapes.open[..]) {
+        return Vec::new();
+    }
+    let found = match conversion.reader {
+        Reader::Builtin(scheme) => dero::ambiguities_with(text, scheme),
+        Reader::Table(ref scheme) => dero::ambiguities_with(text, scheme),
+    };
+    found.into_iter().filter(|a| a.kind == dero::AmbiguityKind::MovedFinal).collect()
+}
+
+/// Asks whether text with the given ambiguities should be saved anyway.
+/// Without a terminal to ask on, it is not saved.
+fn confirm_save(ambiguities: &[Ambiguity]) -> bool {
+    if ambiguities.is_empty() {
+        return true;
+    }
+    if !io::stdin().is_terminal() {
+        eprintln!("Not saved, as the text might not be what was meant.");
+        return false;
+    }
+    eprint!("Save anyway? [y/N] ");
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok() && answer.trim().eq_ignore_ascii_case("y")
+}
+
+/// Deromanizes the prose of a Markdown file and writes the document to the
+/// output file, or else prints it.
+fn convert_markdown(path: &str, reader: &Reader, output: Option<&str>) -> bool {
+    let mut text = String::new();
+    if let Err(err) = File::open(path).and_then(|mut file| file.read_to_string(&mut text)) {
+        eprintln!("Could not read '{}': {}", path, err);
+        return false;
+    }
+    let converted = match *reader {
+        Reader::Builtin(scheme) => dero::deromanize_markdown_with(&text, scheme),
+        Reader::Table(ref scheme) => dero::deromanize_markdown_with(&text, scheme),
+    };
+    match output {
+        Some(output) => match File::create(output).and_then(|mut file| file.write_all(converted.as_bytes())) {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("Could not write '{}': {}", output, err);
+                false
+            }
+        },
+        None => {
+            print!("{}", converted);
+            true
+        }
+    }
+}
+
+/// Converts text line by line, for use in scripts and pipes.
+fn convert_lines<R: BufRead, W: Write>(mut input: R, mut output: W, conversion: &Conversion) -> io::Result<()> {
+    let mut line = String::new();
+    while input.read_line(&mut line)? > 0 {
+        output.write_all(convert(&line, conversion).as_bytes())?;
+        line.clear();
+    }
+    output.flush()
+}
+
+/// Converts the input file (or stdin) to the output file (or stdout).
+fn convert_batch(input: Option<&str>, output: Option<&str>, conversion: &Conversion) -> bool {
+    let reader: Box<dyn BufRead> = match input {
+        Some(path) => match File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(err) => {
+                eprintln!("Could not open '{}': {}", path, err);
+                return false;
+            }
+        },
+        None => Box::new(BufReader::new(io::stdin())),
+    };
+    let writer: Box<dyn Write> = match output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(err) => {
+                eprintln!("Could not create '{}': {}", path, err);
+                return false;
+            }
+        },
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+    match convert_lines(reader, writer, conversion) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("Could not convert the text: {}", err);
+            false
+        }
+    }
+}
+
+/// Converts all of the input to the output at once. Everything but the
+/// converted text is kept exactly as it is.
+fn filter_stream<R: Read, W: Write>(mut input: R, mut output: W, conversion: &Conversion) -> Result<(), String> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes).map_err(|err| format!("could not read the input: {}", err))?;
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(err) => {
+            let valid = &err.as_bytes()[..err.utf8_error().valid_up_to()];
+            let line = valid.iter().filter(|&&byte| byte == b'\n').count() + 1;
+            return Err(format!("input is not valid UTF-8 (line {}, byte {})", line, valid.len()));
+        }
+    };
+    output.write_all(convert(&text, conversion).as_bytes())
+        .and_then(|_| output.flush())
+        .map_err(|err| format!("could not write the output: {}", err))
+}
+
+/// Converts all of stdin to stdout, as an editor filter.
+fn filter(conversion: &Conversion) -> bool {
+    let stdout = io::stdout();
+    match filter_stream(io::stdin(), stdout.lock(), conversion) {
+        Ok(()) => true,
+        Err(message) => {
+            eprintln!("dero: {}", message);
+            false
+        }
+    }
+}
+
+fn convert_single(text: &str, conversion: &Conversion, copy: bool, look_up: bool, append_file: Option<String>, anki: bool) -> bool {
+    let ambiguities = moved_finals(text, conversion);
+    let output = convert(text, conversion);
     println!("{}", &output);
+    for ambiguity in &ambiguities {
+        eprintln!("Did you mean {} ({})?", ambiguity.hangeul, ambiguity.romaja);
+    }
+    let saves = copy || look_up || anki || append_file.is_some();
+    if saves && !confirm_save(&ambiguities) {
+        return true;
+    }
     if copy {
         copy_to_clipboard(&output);
     }
@@ -108,9 +318,32 @@ fn append_to_file(file: &Path, text: &str) {
     }
 }
 
-const USAGE: &'static str = "Usage: dero [--help | OPTIONS]";
+const USAGE: &'static str = "Usage: dero [filter] [--help | OPTIONS] [TEXT... | -]";
 
-const HELP: &'static str = r#"Optional arguments:
+const HELP: &'static str = r#"Converts each TEXT, or else starts an interactive prompt. When stdin is not
+a terminal, or '-' is given, stdin is converted line by line instead.
+
+'dero filter' converts all of stdin at once and writes it to stdout, keeping
+everything else byte for byte, for use as an editor filter.
+
+Optional arguments:
+  --stdin               Convert stdin line by line (same as '-').
+  --input | -i FILE     Convert the lines of FILE rather than stdin.
+  --output | -o FILE    Write the converted text to FILE rather than stdout.
+  --markdown FILE       Deromanize the prose of the Markdown FILE, leaving
+                        code, links, HTML and front matter untouched, and
+                        print the document.
+  --scheme | -s FILE    Read romaja using the scheme in the mapping FILE.
+  --delimiters 'OPEN CLOSE'
+                        Leave text between OPEN and CLOSE untouched, rather
+                        than text between '[' and ']'.
+  --escape-char CHAR    Make the character after CHAR literal (none by
+                        default).
+  --marked              Only convert the text between the delimiters ('{{'
+                        and '}}' by default), leaving everything else as is.
+  --ipa                 Print the converted text as IPA, as it is pronounced
+                        (not with --markdown or the interactive prompt).
+  --narrow              Print a narrow IPA transcription (implies --ipa).
   --look-up | -l TEXT   Deromanize TEXT and look up the result in the OS X
                         dictionary.
   --anki | -n           Activate ANKI after converting the text.
@@ -127,10 +360,30 @@ fn main() {
     let a_no_copy = ArgDef::named("no-copy").switch();
     let a_version = ArgDef::named("version").switch();
     let a_append = ArgDef::named_and_short("append-to-file", 'a').option();
+    let a_scheme = ArgDef::named_and_short("scheme", 's').option();
+    let a_delimiters = ArgDef::named("delimiters").option();
+    let a_escape_char = ArgDef::named("escape-char").option();
+    let a_markdown = ArgDef::named("markdown").option();
+    let a_marked = ArgDef::named("marked").switch();
+    let a_ipa = ArgDef::named("ipa").switch();
+    let a_narrow = ArgDef::named("narrow").switch();
+    let a_stdin = ArgDef::named("stdin").switch();
+    let a_input = ArgDef::named_and_short("input", 'i').option();
+    let a_output = ArgDef::named_and_short("output", 'o').option();
     let a_help = ArgDef::named_and_short("help", 'h').switch();
-    let expected = &[a_text_parts, a_append, a_anki, a_lookup, a_version, a_help, a_no_copy];
+    let expected = &[a_text_parts, a_stdin, a_input, a_output, a_append, a_scheme, a_delimiters, a_escape_char, a_markdown, a_marked,
+                     a_ipa, a_narrow,
+                     a_anki, a_lookup, a_version, a_help, a_no_copy];
 
-    let args: Vec<_> = env::args().skip(1).collect();
+    let mut args: Vec<_> = env::args().skip(1).collect();
+    let filter_mode = args.first().is_some_and(|arg| arg == "filter");
+    if filter_mode {
+        args.remove(0);
+    }
+    // A lone '-' is not understood by the argument parser
+    let args: Vec<_> = args.into_iter()
+        .map(|arg| if arg == "-" { "--stdin".to_string() } else { arg })
+        .collect();
     let parse = Parse::new(expected, &args).expect("Invalid definitions");
 
     let mut parts = Vec::new();
@@ -138,6 +391,15 @@ fn main() {
     let mut look_up = false;
     let mut append_file = None;
     let mut anki = false;
+    let mut reader = Reader::Builtin(InputScheme::Dero);
+    let mut delimiters = None;
+    let mut escape_char = None;
+    let mut markdown = None;
+    let mut marked = false;
+    let mut ipa = None;
+    let mut stdin = false;
+    let mut input = None;
+    let mut output = None;
 
     for item in parse {
         match item {
@@ -161,6 +423,30 @@ fn main() {
             Ok(Option("append-to-file", value)) => {
                 append_file = Some(value.to_string());
             }
+            Ok(Option("scheme", value)) => {
+                reader = load_scheme(value);
+            }
+            Ok(Option("delimiters", value)) => {
+                delimiters = Some(parse_delimiters(value));
+            }
+            Ok(Switch("marked")) => marked = true,
+            Ok(Switch("ipa")) => {
+                ipa = ipa.or(Some(Transcription::Broad));
+            }
+            Ok(Switch("narrow")) => ipa = Some(Transcription::Narrow),
+            Ok(Switch("stdin")) => stdin = true,
+            Ok(Option("input", value)) => {
+                input = Some(value);
+            }
+            Ok(Option("output", value)) => {
+                output = Some(value);
+            }
+            Ok(Option("markdown", value)) => {
+                markdown = Some(value);
+            }
+            Ok(Option("escape-char", value)) => {
+                escape_char = Some(parse_escape_char(value));
+            }
             Ok(Switch("help")) => {
                 println!("{}\n\n{}", USAGE, HELP);
                 return;
@@ -173,11 +459,73 @@ fn main() {
         }
     }
 
+    if filter_mode {
+        // The filter only reads stdin and writes stdout, and only changes the
+        // text where it is converted
+        let unsupported = [
+            (!parts.is_empty(), "TEXT"),
+            (stdin, "'-' or --stdin"),
+            (input.is_some(), "--input"),
+            (output.is_some(), "--output"),
+            (markdown.is_some(), "--markdown"),
+            (escape_char.is_some(), "--escape-char"),
+            (append_file.is_some(), "--append-to-file"),
+            (look_up, "--look-up"),
+            (anki, "--anki"),
+        ];
+        if let Some(&(_, name)) = unsupported.iter().find(|&&(given, _)| given) {
+            eprintln!("{} cannot be used with 'dero filter'", name);
+            process::exit(2);
+        }
+    }
+
+    if let Some(path) = markdown {
+        if ipa.is_some() {
+            eprintln!("--ipa and --narrow cannot be used with --markdown");
+            process::exit(2);
+        }
+        if !convert_markdown(path, &reader, output) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    let mut escapes = delimiters.unwrap_or_else(|| {
+        if marked { EscapeConfig::marked() } else { EscapeConfig::default() }
+    });
+    if let Some(escape) = escape_char {
+        escapes.escape = escape;
+    }
+    let conversion = Conversion { reader, escapes, marked, ipa };
+
+    if filter_mode {
+        if !filter(&conversion) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    if !parts.is_empty() && (stdin || input.is_some() || output.is_some()) {
+        eprintln!("TEXT cannot be given with '-', --stdin, --input or --output");
+        process::exit(2);
+    }
+    let piped = parts.is_empty() && !io::stdin().is_terminal();
+    if stdin || input.is_some() || output.is_some() || piped {
+        if !convert_batch(input, output, &conversion) {
+            process::exit(1);
+        }
+        return;
+    }
+
     if parts.is_empty() {
+        if conversion.ipa.is_some() {
+            eprintln!("--ipa and --narrow cannot be used with the interactive prompt");
+            process::exit(2);
+        }
         let message = "Welcome to dero. Use Ctrl-C to quit.\
         \nWrite romaja to convert it to 한글.";
         let prompt = "dero: ";
-        dero::start_interactive_loop(message, prompt, &mut |hangeul| {
+        let mut on_deromanize = |hangeul: &str| {
             if copy_text {
                 copy_to_clipboard(&hangeul);
             }
@@ -194,14 +542,96 @@ fn main() {
                 let path = Path::new(file);
                 append_to_file(path, &hangeul);
             }
-        });
+        };
+        let escapes = &conversion.escapes;
+        let marked = conversion.marked;
+        match conversion.reader {
+            Reader::Builtin(scheme) => {
+                dero::start_interactive_loop_with(message, prompt, scheme, escapes, marked, &mut on_deromanize);
+            }
+            Reader::Table(ref scheme) => {
+                dero::start_interactive_loop_with(message, prompt, scheme, escapes, marked, &mut on_deromanize);
+            }
+        }
         return;
     } else {
         for part in parts {
-            if convert_single(part, copy_text, look_up, append_file.clone(), anki) != true {
+            if convert_single(part, &conversion, copy_text, look_up, append_file.clone(), anki) != true {
                 process::exit(1);
             }
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use dero::{EscapeConfig, InputScheme};
+    use dero::Transcription;
+    use super::{convert_lines, filter_stream, Conversion, Reader};
+
+    fn conversion() -> Conversion {
+        Conversion {
+            reader: Reader::Builtin(InputScheme::Dero),
+            escapes: EscapeConfig::default(),
+            marked: false,
+            ipa: None,
+        }
+    }
+
+    fn lines(input: &str, conversion: &Conversion) -> String {
+        let mut output = Vec::new();
+        convert_lines(input.as_bytes(), &mut output, conversion).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn converts_each_line() {
+        let conversion = conversion();
+        assert_eq!(lines("annyeox\nhangyl [x]\n", &conversion), "안녕\n한글 x\n");
+        // A final consonant does not move on to the next line
+        assert_eq!(lines("dal\nai", &conversion), "달\n아이");
+        assert_eq!(lines("", &conversion), "");
+    }
+
+    #[test]
+    fn transcribes_each_line() {
+        let mut conversion = conversion();
+        conversion.ipa = Some(Transcription::Narrow);
+        assert_eq!(lines("hakgyo\nsarax\n", &conversion), "hak̚.k͈jo\nsa.ɾaŋ\n");
+    }
+
+    #[test]
+    fn keeps_line_endings() {
+        let conversion = conversion();
+        assert_eq!(lines("ga\r\nna\r\n", &conversion), "가\r\n나\r\n");
+        assert_eq!(lines("ga\n\nna", &conversion), "가\n\n나");
+    }
+
+    fn filtered(input: &[u8], conversion: &Conversion) -> Result<String, String> {
+        let mut output = Vec::new();
+        filter_stream(input, &mut output, conversion)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn filter_keeps_bytes() {
+        let conversion = conversion();
+        assert_eq!(filtered(b"ga\r\nna\r\n", &conversion).unwrap(), "가\r\n나\r\n");
+        assert_eq!(filtered(b"dal\ngi\n", &conversion).unwrap(), "달\n기\n");
+        assert_eq!(filtered(b"hangyl", &conversion).unwrap(), "한글");
+        assert_eq!(filtered(b"C:\\dir [x] ga", &conversion).unwrap(), "C:\\딜 x 가");
+    }
+
+    #[test]
+    fn filter_rejects_invalid_utf8() {
+        let err = filtered(b"ga\nna \xff", &conversion()).unwrap_err();
+        assert_eq!(err, "input is not valid UTF-8 (line 2, byte 6)");
+    }
+
+    #[test]
+    fn filter_transcribes() {
+        let mut conversion = conversion();
+        conversion.ipa = Some(Transcription::Broad);
+        assert_eq!(filtered(b"hangugeo\n", &conversion).unwrap(), "han.ɡu.ɡʌ\n");
+    }
+}
@@ -0,0 +1,328 @@
+//! Deromanization of the prose in Markdown documents.
+//!
+//! Everything that is not prose is left exactly as it is: front matter,
+//! fenced and indented code blocks, link reference definitions, code spans,
+//! autolinks and HTML tags, link targets and reference labels, bare URLs,
+//! HTML entities and backslash escapes. The markup characters themselves
+//! are not romaja, so they are kept by the conversion of the prose as well.
+//!
+//! Ex: `[hangyl](https://ko.wikipedia.org)` => `[한글](https://ko.wikipedia.org)`
+
+use std::ops::Range;
+
+use super::{deromanize_with_into, ConversionScheme, InputScheme};
+
+/// The URL prefixes that start a bare link.
+const URL_PREFIXES: &[&str] = &["https://", "http://", "ftp://", "mailto:", "www."];
+
+/// Converts the prose of the given Markdown document to 한글.
+pub fn deromanize_markdown(text: &str) -> String {
+    deromanize_markdown_with(text, InputScheme::Dero)
+}
+
+/// Converts the prose of the given Markdown document to 한글, reading it as
+/// the given romaja scheme. See `deromanize_markdown`.
+pub fn deromanize_markdown_with<S: ConversionScheme>(text: &str, scheme: S) -> String {
+    let mut s = String::new();
+    for (range, is_prose) in prose_ranges(text) {
+        if is_prose {
+            deromanize_with_into(&text[range], &scheme, &mut s);
+        } else {
+            s.push_str(&text[range]);
+        }
+    }
+    s
+}
+
+/// Splits the document into ranges of prose and of text to keep.
+fn prose_ranges(text: &str) -> Vec<(Range<usize>, bool)> {
+    let mut ranges = Vec::new();
+    let mut i = front_matter_len(text);
+    keep(&mut ranges, 0..i);
+    let mut paragraph: Option<usize> = None;
+    let mut fence: Option<(char, usize)> = None;
+    let mut after_blank = true;
+    let mut in_code = false;
+    while i < text.len() {
+        let end = text[i..].find('\n').map_or(text.len(), |pos| i + pos + 1);
+        let line = &text[i..end];
+        let blank = line.trim().is_empty();
+        let special = if let Some((ch, len)) = fence {
+            if closes_fence(line, ch, len) {
+                fence = None;
+            }
+            true
+        } else if let Some(opened) = opening_fence(line) {
+            fence = Some(opened);
+            true
+        } else if !blank && (after_blank || in_code) && paragraph.is_none() && is_indented(line) {
+            in_code = true;
+            true
+        } else {
+            in_code = in_code && blank;
+            is_reference_definition(line)
+        };
+        if special || blank {
+            if let Some(start) = paragraph.take() {
+                inline_ranges(text, start..i, &mut ranges);
+            }
+            keep(&mut ranges, i..end);
+        } else if paragraph.is_none() {
+            paragraph = Some(i);
+        }
+        after_blank = blank;
+        i = end;
+    }
+    if let Some(start) = paragraph {
+        inline_ranges(text, start..text.len(), &mut ranges);
+    }
+    ranges
+}
+
+fn keep(ranges: &mut Vec<(Range<usize>, bool)>, range: Range<usize>) {
+    push_range(ranges, range, false);
+}
+
+/// Adds a range, joining it with the last one if they are of the same kind.
+fn push_range(ranges: &mut Vec<(Range<usize>, bool)>, range: Range<usize>, is_prose: bool) {
+    if range.start == range.end {
+        return;
+    }
+    if let Some(&mut (ref mut last, last_is_prose)) = ranges.last_mut() {
+        if last_is_prose == is_prose && last.end == range.start {
+            last.end = range.end;
+            return;
+        }
+    }
+    ranges.push((range, is_prose));
+}
+
+/// The length of the YAML (`---`) or TOML (`+++`) front matter of a document.
+fn front_matter_len(text: &str) -> usize {
+    let marker = if text.starts_with("---\n") || text.starts_with("---\r\n") {
+        "---"
+    } else if text.starts_with("+++\n") || text.starts_with("+++\r\n") {
+        "+++"
+    } else {
+        return 0;
+    };
+    let mut i = text.find('\n').unwrap() + 1;
+    while i < text.len() {
+        let end = text[i..].find('\n').map_or(text.len(), |pos| i + pos + 1);
+        let line = text[i..end].trim_end();
+        if line == marker || (marker == "---" && line == "...") {
+            return end;
+        }
+        i = end;
+    }
+    0
+}
+
+/// Removes up to three spaces of indentation.
+fn unindent(line: &str) -> Option<&str> {
+    let spaces = line.len() - line.trim_start_matches(' ').len();
+    if spaces <= 3 {
+        Some(&line[spaces..])
+    } else {
+        None
+    }
+}
+
+fn is_indented(line: &str) -> bool {
+    line.starts_with("    ") || line.starts_with('\t')
+}
+
+/// Returns the character and length of the code fence the line opens.
+fn opening_fence(line: &str) -> Option<(char, usize)> {
+    let rest = unindent(line)?;
+    let ch = rest.chars().next().filter(|&ch| ch == '`' || ch == '~')?;
+    let len = rest.len() - rest.trim_start_matches(ch).len();
+    let info = &rest[len..];
+    if len >= 3 && !(ch == '`' && info.contains('`')) {
+        Some((ch, len))
+    } else {
+        None
+    }
+}
+
+fn closes_fence(line: &str, ch: char, len: usize) -> bool {
+    match unindent(line) {
+        Some(rest) => {
+            let after = rest.trim_start_matches(ch);
+            rest.len() - after.len() >= len && after.trim().is_empty()
+        }
+        None => false,
+    }
+}
+
+/// Returns whether the line is a link reference definition (`[label]: url`).
+fn is_reference_definition(line: &str) -> bool {
+    match unindent(line).and_then(|rest| rest.strip_prefix('[')) {
+        Some(rest) => match rest.find(']') {
+            Some(end) => end > 0 && rest[end + 1..].starts_with(':'),
+            None => false,
+        },
+        None => false,
+    }
+}
+
+/// Returns the length of the text to keep at the start of the text, if any.
+fn inline_kept_len(text: &str, at_word_start: bool) -> Option<usize> {
+    let mut chars = text.chars();
+    let first = chars.next()?;
+    let second = chars.next();
+    match first {
+        // Backslash escapes
+        '\\' => second.filter(|ch| ch.is_ascii_punctuation()).map(|_| 2),
+        // Code spans, or an unmatched backtick run
+        '`' => {
+            let run = text.len() - text.trim_start_matches('`').len();
+            let mut i = run;
+            while let Some(pos) = text[i..].find('`') {
+                let start = i + pos;
+                let len = text[start..].len() - text[start..].trim_start_matches('`').len();
+                if len == run {
+                    return Some(start + len);
+                }
+                i = start + len;
+            }
+            Some(run)
+        }
+        // Autolinks, HTML tags and comments
+        '<' => {
+            if text.starts_with("<!--") {
+                return text.find("-->").map(|end| end + 3);
+            }
+            let tag = second.is_some_and(|ch| ch.is_ascii_alphabetic() || "/!?".contains(ch));
+            let end = text.find('>')?;
+            if tag && !text[..end].contains('\n') {
+                Some(end + 1)
+            } else {
+                None
+            }
+        }
+        // Link targets and reference labels
+        ']' => match second {
+            Some('(') => closing_paren(&text[1..]).map(|end| end + 1),
+            Some('[') => text[1..].find(']').map(|end| end + 2),
+            _ => None,
+        },
+        // HTML entities
+        '&' => {
+            let end = text.find(';')?;
+            let name = &text[1..end];
+            let valid = name.strip_prefix('#').unwrap_or(name);
+            if !valid.is_empty() && valid.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+                Some(end + 1)
+            } else {
+                None
+            }
+        }
+        // Bare URLs
+        _ if at_word_start && URL_PREFIXES.iter().any(|prefix| text.starts_with(prefix)) => {
+            let end = text.find(|ch: char| ch.is_whitespace() || ch == '<').unwrap_or(text.len());
+            let url = text[..end].trim_end_matches(|ch| ".,:;!?\"'*_".contains(ch));
+            // A closing parenthesis of the surrounding text
+            let opened = url.matches('(').count();
+            let mut url = url;
+            while url.ends_with(')') && url.matches(')').count() > opened {
+                url = &url[..url.len() - 1];
+            }
+            Some(url.len())
+        }
+        _ => None,
+    }
+}
+
+/// Returns the position after the parenthesis that closes the one the text
+/// starts with.
+fn closing_paren(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, ch) in text.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits the inline content of a paragraph into prose and text to keep.
+fn inline_ranges(text: &str, paragraph: Range<usize>, ranges: &mut Vec<(Range<usize>, bool)>) {
+    let mut i = paragraph.start;
+    let mut prev = None;
+    while i < paragraph.end {
+        let rest = &text[i..paragraph.end];
+        let at_word_start = !prev.is_some_and(|ch: char| ch.is_alphanumeric());
+        if let Some(len) = inline_kept_len(rest, at_word_start) {
+            // The bracket before a link target belongs to the link text
+            let (prose, kept) = if rest.starts_with(']') { (1, len - 1) } else { (0, len) };
+            push_range(ranges, i..i + prose, true);
+            keep(ranges, i + prose..i + prose + kept);
+            prev = rest[..len].chars().last();
+            i += len;
+        } else {
+            let ch = rest.chars().next().unwrap();
+            push_range(ranges, i..i + ch.len_utf8(), true);
+            prev = Some(ch);
+            i += ch.len_utf8();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::deromanize_markdown;
+
+    #[test]
+    fn prose_is_converted() {
+        let text = "# hangyl\n\n*annyeoxhaseyo*, jeonyn **jeimsy**ibnida.\n\n- sagwa\n- bae\n";
+        let expected = "# 한글\n\n*안녕하세요*, 저는 **제임스**입니다.\n\n- 사과\n- 배\n";
+        assert_eq!(deromanize_markdown(text), expected);
+    }
+
+    #[test]
+    fn code_is_kept() {
+        let text = "igeo `code span` da\n\n```rust\nfn main() {}\n```\n\n    indented code\n\nGyt\n";
+        let expected = "이거 `code span` 다\n\n```rust\nfn main() {}\n```\n\n    indented code\n\n끝\n";
+        assert_eq!(deromanize_markdown(text), expected);
+        assert_eq!(deromanize_markdown("``a ` b`` ga"), "``a ` b`` 가");
+        assert_eq!(deromanize_markdown("~~~\nga\n"), "~~~\nga\n");
+    }
+
+    #[test]
+    fn links_are_kept() {
+        let text = "[hangyl](https://ko.wikipedia.org/wiki/(x) \"title\") bogi";
+        let expected = "[한글](https://ko.wikipedia.org/wiki/(x) \"title\") 보기";
+        assert_eq!(deromanize_markdown(text), expected);
+        assert_eq!(deromanize_markdown("![gyrim](a.png) [ga][label]"), "![그림](a.png) [가][label]");
+        assert_eq!(deromanize_markdown("[label]: https://example.com\n"), "[label]: https://example.com\n");
+        assert_eq!(deromanize_markdown("bogi: https://example.com/a_b."), "보기: https://example.com/a_b.");
+        assert_eq!(deromanize_markdown("(www.example.com) <https://x.org>"), "(www.example.com) <https://x.org>");
+    }
+
+    #[test]
+    fn html_and_escapes_are_kept() {
+        assert_eq!(deromanize_markdown("<b class=\"x\">ga</b> &amp; \\*na\\*"), "<b class=\"x\">가</b> &amp; \\*나\\*");
+        assert_eq!(deromanize_markdown("<!-- note -->ga"), "<!-- note -->가");
+        assert_eq!(deromanize_markdown("a < b"), "아 < ㅂ");
+    }
+
+    #[test]
+    fn front_matter_is_kept() {
+        let text = "---\ntitle: notes\n---\nhangyl\n";
+        assert_eq!(deromanize_markdown(text), "---\ntitle: notes\n---\n한글\n");
+        let toml = "+++\ntitle = \"notes\"\n+++\nga";
+        assert_eq!(deromanize_markdown(toml), "+++\ntitle = \"notes\"\n+++\n가");
+        assert_eq!(deromanize_markdown("---\nga"), "---\n가");
+    }
+}
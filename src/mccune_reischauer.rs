@@ -0,0 +1,163 @@
+//! Conversion from 한글 to the McCune–Reischauer romanization.
+//!
+//! Like the Revised Romanization this follows the pronunciation of each word,
+//! but it writes the voicing of plain consonants (`부산` => `pusan`,
+//! `갈비` => `kalbi`), marks aspiration with apostrophes (`김치` =>
+//! `kimch'i`) and uses breves for ㅓ and ㅡ (`한글` => `han'gŭl`).
+
+use hangeul2::{Initial, Vowel, Final};
+
+use super::sound::{self, Piece};
+
+/// Returns the letters of an initial, given whether it follows a voiced
+/// sound within the word.
+fn initial_letters(ini: Initial, voiced: bool) -> &'static str {
+    use hangeul2::Initial::*;
+    match ini {
+        G     => if voiced { "g" } else { "k" },
+        Gg    => "kk",
+        N     => "n",
+        D     => if voiced { "d" } else { "t" },
+        Dd    => "tt",
+        R     => "r",
+        M     => "m",
+        B     => if voiced { "b" } else { "p" },
+        Bb    => "pp",
+        S     => "s",
+        Ss    => "ss",
+        Ieung => "",
+        J     => if voiced { "j" } else { "ch" },
+        Jj    => "tch",
+        Ch    => "ch'",
+        K     => "k'",
+        T     => "t'",
+        P     => "p'",
+        H     => "h",
+    }
+}
+
+fn vowel_letters(vow: Vowel) -> &'static str {
+    use hangeul2::Vowel::*;
+    match vow {
+        A   => "a",
+        Ae  => "ae",
+        Ya  => "ya",
+        Yae => "yae",
+        Eo  => "ŏ",
+        E   => "e",
+        Yeo => "yŏ",
+        Ye  => "ye",
+        O   => "o",
+        Wa  => "wa",
+        Wae => "wae",
+        Oe  => "oe",
+        Yo  => "yo",
+        U   => "u",
+        Weo => "wŏ",
+        We  => "we",
+        Wi  => "wi",
+        Yu  => "yu",
+        Y   => "ŭ",
+        Yi  => "ŭi",
+        I   => "i",
+    }
+}
+
+fn final_letters(fin: Final) -> &'static str {
+    use hangeul2::Final::*;
+    match sound::neutralize(fin) {
+        G     => "k",
+        N     => "n",
+        D     => "t",
+        L     => "l",
+        M     => "m",
+        B     => "p",
+        Ieung => "ng",
+        _     => "",
+    }
+}
+
+/// Converts the 한글 in the given text to the McCune–Reischauer
+/// romanization. Characters that are not 한글 syllables are left untouched.
+///
+/// Ex: `대한민국` => `taehanmin'guk`
+pub fn romanize(text: &str) -> String {
+    let mut s = String::new();
+    romanize_into(text, &mut s);
+    s
+}
+
+/// Converts the 한글 in the given text to the McCune–Reischauer
+/// romanization. See `romanize`.
+pub fn romanize_into(text: &str, s: &mut String) {
+    use hangeul2::Initial::*;
+    for piece in sound::pieces(text) {
+        let mut word = match piece {
+            Piece::Word(word) => word,
+            Piece::Other(ch) => {
+                s.push(ch);
+                continue;
+            }
+        };
+        sound::pronounce_word(&mut word);
+        let mut prev = None;
+        for block in &word {
+            // Plain consonants are voiced after vowels, ㄴ, ㄹ, ㅁ and ㅇ
+            let voiced = match prev {
+                Some(fin) => !matches!(fin, Final::G | Final::D | Final::B),
+                None => false,
+            };
+            match (prev, block.initial) {
+                // ㄴㄱ is written n'g, to tell it apart from ㅇ
+                (Some(Final::N), G) => s.push_str("'g"),
+                (Some(Final::L), R) => s.push('l'),
+                (_, S) if block.vowel == Vowel::I || block.vowel == Vowel::Wi => {
+                    s.push_str("sh");
+                }
+                (_, ini) => s.push_str(initial_letters(ini, voiced)),
+            }
+            s.push_str(vowel_letters(block.vowel));
+            s.push_str(final_letters(block.fin));
+            prev = Some(block.fin);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::romanize;
+
+    #[test]
+    fn examples() {
+        let examples = [
+            ("한글", "han'gŭl"),
+            ("한국", "han'guk"),
+            ("대한민국", "taehanmin'guk"),
+            ("서울", "sŏul"),
+            ("부산", "pusan"),
+            ("평양", "p'yŏngyang"),
+            ("조선", "chosŏn"),
+            ("청진", "ch'ŏngjin"),
+            ("금강산", "kŭmgangsan"),
+            ("백두산", "paektusan"),
+            ("한라산", "hallasan"),
+            ("갈비", "kalbi"),
+            ("국밥", "kukpap"),
+            ("김치", "kimch'i"),
+            ("진짜", "chintcha"),
+            ("시", "shi"),
+            ("독립", "tongnip"),
+            ("종로", "chongno"),
+            ("같이", "kach'i"),
+            ("의사", "ŭisa"),
+        ];
+        for &(hangeul, expected) in &examples {
+            assert_eq!(romanize(hangeul), expected, "{}", hangeul);
+        }
+    }
+
+    #[test]
+    fn words_start_voiceless() {
+        assert_eq!(romanize("대한 민국"), "taehan min'guk");
+    }
+}
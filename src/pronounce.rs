@@ -0,0 +1,132 @@
+//! The pronunciation of 한글 text.
+//!
+//! Each word is changed into the syllables it is pronounced as, following the
+//! standard pronunciation: finals move on to a following vowel (연음), stops
+//! before nasals become nasals (비음화), ㄴ next to ㄹ becomes ㄹ (유음화),
+//! plain consonants after stops become tense (경음화), ㅎ is dropped or
+//! aspirates the consonant next to it, and ㄷ and ㅌ before 이 become ㅈ and ㅊ
+//! (구개음화).
+//!
+//! Ex: `국물` => `궁물`, `같이` => `가치`, `학교` => `학꾜`
+//!
+//! Changes that depend on the meaning of a word, such as the tense consonants
+//! of compound nouns (`눈동자` => `눈똥자`), are not made.
+
+use hangeul2::{Initial, Final, Block};
+
+use super::sound::{self, Piece};
+
+/// Returns the tense counterpart of a plain consonant.
+fn tense(ini: Initial) -> Option<Initial> {
+    use hangeul2::Initial::*;
+    match ini {
+        G => Some(Gg),
+        D => Some(Dd),
+        B => Some(Bb),
+        S => Some(Ss),
+        J => Some(Jj),
+        _ => None,
+    }
+}
+
+/// Returns whether the initial after the given final becomes tense, given
+/// the final that is left after the other sound changes.
+fn tensifies(original: Final, pronounced: Final) -> bool {
+    use hangeul2::Final::*;
+    // Clusters that end verb stems tensify as well (읽고 => 일꼬, 앉다 => 안따)
+    matches!(pronounced, G | D | B) || matches!(original, Lg | Lb | Lt | Lm | Nj)
+}
+
+/// Returns the syllables that the given word is pronounced as.
+///
+/// Ex: `[국, 물]` => `[궁, 물]`
+pub fn pronounce_blocks(word: &[Block]) -> Vec<Block> {
+    let mut pronounced = word.to_vec();
+    sound::pronounce_word(&mut pronounced);
+    for i in 1..word.len() {
+        let moved = word[i].initial == Initial::Ieung;
+        if moved && pronounced[i].initial == Initial::Ieung {
+            continue;
+        }
+        let original = if moved { Final::Empty } else { word[i - 1].fin };
+        if tensifies(original, pronounced[i - 1].fin) {
+            if let Some(ini) = tense(pronounced[i].initial) {
+                pronounced[i].initial = ini;
+            }
+        }
+    }
+    pronounced
+}
+
+/// Converts the given 한글 text into the way it is pronounced. Every word is
+/// pronounced on its own, and other characters are kept as they are.
+///
+/// Ex: `같이 먹어요` => `가치 머거요`
+pub fn pronounce(text: &str) -> String {
+    let mut s = String::new();
+    for piece in sound::pieces(text) {
+        match piece {
+            Piece::Word(word) => s.extend(pronounce_blocks(&word).iter().map(|block| block.combine())),
+            Piece::Other(ch) => s.push(ch),
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use hangeul2::Block;
+    use super::{pronounce, pronounce_blocks};
+
+    #[test]
+    fn rules() {
+        let examples = [
+            // 연음
+            ("먹어요", "머거요"),
+            ("옷이", "오시"),
+            ("값이", "갑씨"),
+            // 비음화
+            ("국물", "궁물"),
+            ("십만", "심만"),
+            ("독립", "동닙"),
+            // 유음화
+            ("신라", "실라"),
+            ("칼날", "칼랄"),
+            // 경음화
+            ("학교", "학꾜"),
+            ("국밥", "국빱"),
+            ("있다", "읻따"),
+            ("읽고", "일꼬"),
+            ("앉다", "안따"),
+            ("밟다", "밥따"),
+            ("밟는", "밤는"),
+            ("밟고", "밥꼬"),
+            // ㅎ
+            ("좋고", "조코"),
+            ("좋아", "조아"),
+            ("좋소", "조쏘"),
+            ("축하", "추카"),
+            ("않는", "안는"),
+            // 구개음화
+            ("같이", "가치"),
+            ("굳이", "구지"),
+            ("닫히다", "다치다"),
+        ];
+        for &(word, expected) in &examples {
+            assert_eq!(pronounce(word), expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn blocks() {
+        let word: Vec<_> = "값".chars().map(|ch| Block::from_char(ch).unwrap()).collect();
+        let pronounced: String = pronounce_blocks(&word).iter().map(|block| block.combine()).collect();
+        assert_eq!(pronounced, "갑");
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(pronounce("같이 먹어요!"), "가치 머거요!");
+        assert_eq!(pronounce("ㅋㅋ 부엌에서, abc"), "ㅋㅋ 부어케서, abc");
+    }
+}
@@ -0,0 +1,351 @@
+//! Conversion from 한글 to the official Revised Romanization of Korean.
+//!
+//! The romanization follows the pronunciation of each word, so sound changes
+//! such as liaison (`설악` => `seorak`), nasalization (`종로` => `jongno`),
+//! liquidization (`신라` => `silla`), aspiration (`좋고` => `joko`) and
+//! palatalization (`같이` => `gachi`) are applied within words. Tensing is
+//! not written, as per the standard.
+//!
+//! The module also contains the readers used by `InputScheme::Revised`, which
+//! read the romanization back in the same manner as the `read_*` functions of
+//! the crate root.
+
+use hangeul2::{Initial, Vowel, Final};
+
+use super::sound::{self, Piece};
+
+fn initial_letters(ini: Initial) -> &'static str {
+    use hangeul2::Initial::*;
+    match ini {
+        G     => "g",
+        Gg    => "kk",
+        N     => "n",
+        D     => "d",
+        Dd    => "tt",
+        R     => "r",
+        M     => "m",
+        B     => "b",
+        Bb    => "pp",
+        S     => "s",
+        Ss    => "ss",
+        Ieung => "",
+        J     => "j",
+        Jj    => "jj",
+        Ch    => "ch",
+        K     => "k",
+        T     => "t",
+        P     => "p",
+        H     => "h",
+    }
+}
+
+fn vowel_letters(vow: Vowel) -> &'static str {
+    use hangeul2::Vowel::*;
+    match vow {
+        A   => "a",
+        Ae  => "ae",
+        Ya  => "ya",
+        Yae => "yae",
+        Eo  => "eo",
+        E   => "e",
+        Yeo => "yeo",
+        Ye  => "ye",
+        O   => "o",
+        Wa  => "wa",
+        Wae => "wae",
+        Oe  => "oe",
+        Yo  => "yo",
+        U   => "u",
+        Weo => "wo",
+        We  => "we",
+        Wi  => "wi",
+        Yu  => "yu",
+        Y   => "eu",
+        Yi  => "ui",
+        I   => "i",
+    }
+}
+
+fn final_letters(fin: Final) -> &'static str {
+    use hangeul2::Final::*;
+    match sound::neutralize(fin) {
+        G     => "k",
+        N     => "n",
+        D     => "t",
+        L     => "l",
+        M     => "m",
+        B     => "p",
+        Ieung => "ng",
+        _     => "",
+    }
+}
+
+/// Reads a vowel in the Revised Romanization from the given text and returns
+/// the number of chars read. Capital letters are read as lowercase ones.
+pub fn read_vowel(text: &str) -> Option<(Vowel, usize)> {
+    use hangeul2::Vowel::*;
+    let mut chars = text.chars().map(|ch| ch.to_ascii_lowercase());
+    match chars.next() {
+        Some('a') => match chars.next() {
+            Some('e') => Some((Ae, 2)),
+            _ => Some((A, 1)),
+        },
+        Some('e') => match chars.next() {
+            Some('o') => Some((Eo, 2)),
+            Some('u') => Some((Y, 2)),
+            _ => Some((E, 1)),
+        },
+        Some('o') => match chars.next() {
+            Some('e') => Some((Oe, 2)),
+            _ => Some((O, 1)),
+        },
+        Some('u') => match chars.next() {
+            Some('i') => Some((Yi, 2)),
+            _ => Some((U, 1)),
+        },
+        Some('i') => Some((I, 1)),
+        Some('y') => match chars.next() {
+            Some('a') => match chars.next() {
+                Some('e') => Some((Yae, 3)),
+                _ => Some((Ya, 2)),
+            },
+            Some('e') => match chars.next() {
+                Some('o') => Some((Yeo, 3)),
+                _ => Some((Ye, 2)),
+            },
+            Some('o') => Some((Yo, 2)),
+            Some('u') => Some((Yu, 2)),
+            _ => None,
+        },
+        Some('w') => match chars.next() {
+            Some('a') => match chars.next() {
+                Some('e') => Some((Wae, 3)),
+                _ => Some((Wa, 2)),
+            },
+            Some('o') => Some((Weo, 2)),
+            Some('e') => Some((We, 2)),
+            Some('i') => Some((Wi, 2)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Reads a consonant in the Revised Romanization, with the doubled letters
+/// read as tense consonants.
+fn read_consonant(text: &str) -> Option<(Initial, usize)> {
+    use hangeul2::Initial::*;
+    let mut chars = text.chars().map(|ch| ch.to_ascii_lowercase());
+    let first = chars.next();
+    let second = chars.next();
+    let doubled = first.is_some() && second == first;
+    match first {
+        Some('g') => Some((G, 1)),
+        Some('k') if doubled => Some((Gg, 2)),
+        Some('k') => Some((K, 1)),
+        Some('n') => Some((N, 1)),
+        Some('d') => Some((D, 1)),
+        Some('t') if doubled => Some((Dd, 2)),
+        Some('t') => Some((T, 1)),
+        Some('r') | Some('l') => Some((R, 1)),
+        Some('m') => Some((M, 1)),
+        Some('b') => Some((B, 1)),
+        Some('p') if doubled => Some((Bb, 2)),
+        Some('p') => Some((P, 1)),
+        Some('s') if doubled => Some((Ss, 2)),
+        Some('s') => Some((S, 1)),
+        Some('j') if doubled => Some((Jj, 2)),
+        Some('j') => Some((J, 1)),
+        Some('c') => match second {
+            Some('h') => Some((Ch, 2)),
+            _ => None,
+        },
+        Some('h') => Some((H, 1)),
+        _ => None,
+    }
+}
+
+/// Reads an initial consonant in the Revised Romanization from the given text
+/// and returns the number of chars read.
+/// A hyphen before a vowel is read as ㅇ, and a hyphen before a consonant is
+/// read together with it.
+pub fn read_initial(text: &str) -> Option<(Initial, usize)> {
+    if let Some(rem) = text.strip_prefix('-') {
+        if let Some((ini, len)) = read_consonant(rem) {
+            Some((ini, len + 1))
+        } else if read_vowel(rem).is_some() {
+            Some((Initial::Ieung, 1))
+        } else {
+            None
+        }
+    } else {
+        read_consonant(text)
+    }
+}
+
+/// Reads a final consonant in the Revised Romanization from the given text
+/// and returns the number of chars read.
+/// `ng` followed by a vowel is read as ㄴ, so that the ㄱ starts the next
+/// syllable (`hangeul` => `한글`).
+pub fn read_final(text: &str) -> Option<(Final, usize)> {
+    use hangeul2::Initial::*;
+    if text.get(..2).is_some_and(|start| start.eq_ignore_ascii_case("ng")) {
+        return if read_vowel(&text[2..]).is_some() {
+            Some((Final::N, 1))
+        } else {
+            Some((Final::Ieung, 2))
+        };
+    }
+    let (ini, len) = read_consonant(text)?;
+    let fin = match ini {
+        G | K => Final::G,
+        Gg => Final::Gg,
+        N => Final::N,
+        D | T => Final::D,
+        R => Final::L,
+        M => Final::M,
+        B | P => Final::B,
+        S => Final::S,
+        Ss => Final::Ss,
+        J => Final::J,
+        Ch => Final::Ch,
+        H => Final::H,
+        Dd | Bb | Jj | Ieung => return None,
+    };
+    Some((fin, len))
+}
+
+/// Converts the 한글 in the given text to the Revised Romanization.
+/// Characters that are not 한글 syllables are left untouched.
+///
+/// Ex: `안녕하세요` => `annyeonghaseyo`
+pub fn romanize(text: &str) -> String {
+    let mut s = String::new();
+    romanize_into(text, &mut s);
+    s
+}
+
+/// Converts the 한글 in the given text to the Revised Romanization.
+/// See `romanize`.
+pub fn romanize_into(text: &str, s: &mut String) {
+    for piece in sound::pieces(text) {
+        let mut word = match piece {
+            Piece::Word(word) => word,
+            Piece::Other(ch) => {
+                s.push(ch);
+                continue;
+            }
+        };
+        sound::pronounce_word(&mut word);
+        let mut prev = Final::Empty;
+        for block in &word {
+            // ㄹㄹ is written 'll'
+            if block.initial == Initial::R && prev == Final::L {
+                s.push('l');
+            } else {
+                s.push_str(initial_letters(block.initial));
+            }
+            s.push_str(vowel_letters(block.vowel));
+            s.push_str(final_letters(block.fin));
+            prev = block.fin;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::romanize;
+    use super::super::{deromanize_with, InputScheme};
+
+    #[test]
+    fn official_examples() {
+        let examples = [
+            // Vowels and consonants
+            ("한글", "hangeul"),
+            ("서울", "seoul"),
+            ("부산", "busan"),
+            ("구미", "gumi"),
+            ("영동", "yeongdong"),
+            ("의정부", "uijeongbu"),
+            ("광희문", "gwanghuimun"),
+            ("옥천", "okcheon"),
+            ("합덕", "hapdeok"),
+            ("호법", "hobeop"),
+            ("월곶", "wolgot"),
+            ("벚꽃", "beotkkot"),
+            ("한밭", "hanbat"),
+            ("칠곡", "chilgok"),
+            ("임실", "imsil"),
+            ("울산", "ulsan"),
+            ("샛별", "saetbyeol"),
+            ("압구정", "apgujeong"),
+            ("낙동강", "nakdonggang"),
+            ("죽변", "jukbyeon"),
+            ("낙성대", "nakseongdae"),
+            // Liaison
+            ("백암", "baegam"),
+            ("설악", "seorak"),
+            // Nasalization
+            ("백마", "baengma"),
+            ("종로", "jongno"),
+            ("왕십리", "wangsimni"),
+            ("독립", "dongnip"),
+            // Liquidization
+            ("신라", "silla"),
+            ("별내", "byeollae"),
+            ("울릉", "ulleung"),
+            ("대관령", "daegwallyeong"),
+            // Palatalization
+            ("해돋이", "haedoji"),
+            ("같이", "gachi"),
+            ("굳히다", "guchida"),
+            // Aspiration
+            ("좋고", "joko"),
+            ("놓다", "nota"),
+            ("잡혀", "japyeo"),
+            ("낳지", "nachi"),
+        ];
+        for &(hangeul, expected) in &examples {
+            assert_eq!(romanize(hangeul), expected, "{}", hangeul);
+        }
+    }
+
+    #[test]
+    fn rules_stay_within_words() {
+        assert_eq!(romanize("안녕하세요, 신라 여행!"), "annyeonghaseyo, silla yeohaeng!");
+        assert_eq!(romanize("밥 먹어"), "bap meogeo");
+    }
+
+    #[test]
+    fn reading() {
+        let examples = [
+            ("hangeul", "한글"),
+            ("hang-eul", "항을"),
+            ("han-geul", "한글"),
+            ("jung-ang", "중앙"),
+            ("annyeonghaseyo", "안녕하세요"),
+            ("seoul", "서울"),
+            ("bulgogi", "불고기"),
+            ("kkachi", "까치"),
+            ("ttalgi", "딸기"),
+            ("ppang", "빵"),
+            ("jjigae", "찌개"),
+            ("ssal", "쌀"),
+            ("uijeongbu", "의정부"),
+            ("gwanghwamun", "광화문"),
+            ("Hong Gil-dong", "홍 길동"),
+            ("한-ngeul", "한ㄴ글"),
+        ];
+        for &(romaja, expected) in &examples {
+            assert_eq!(deromanize_with(romaja, InputScheme::Revised), expected, "{}", romaja);
+        }
+    }
+
+    #[test]
+    fn romanization_reads_back_without_sound_changes() {
+        for &word in &["한글", "서울", "부산", "영동", "의정부", "광희문", "안녕하세요"] {
+            let romanized = romanize(word);
+            assert_eq!(deromanize_with(&romanized, InputScheme::Revised), word, "{}", romanized);
+        }
+    }
+}
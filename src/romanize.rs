@@ -0,0 +1,261 @@
+//! Conversion from 한글 back into the romaja understood by this library, or
+//! into one of the common romanization systems.
+
+use hangeul2::{Initial, Vowel, Final, Block};
+
+use super::{read_vowel, revised, mccune_reischauer, yale};
+
+/// The romanization systems that 한글 can be converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    /// The romaja read by this library (see `romanize`).
+    Dero,
+    /// The official Revised Romanization of Korean.
+    Revised,
+    /// The McCune–Reischauer romanization.
+    McCuneReischauer,
+    /// The Yale romanization.
+    Yale,
+}
+
+/// The romaja sequence that `read_initial` reads as the given initial.
+fn initial_romaja(ini: Initial) -> &'static str {
+    use hangeul2::Initial::*;
+    match ini {
+        G     => "g",
+        Gg    => "G",
+        N     => "n",
+        D     => "d",
+        Dd    => "D",
+        R     => "r",
+        M     => "m",
+        B     => "b",
+        Bb    => "B",
+        S     => "s",
+        Ss    => "S",
+        Ieung => "x",
+        J     => "j",
+        Jj    => "J",
+        Ch    => "ch",
+        K     => "k",
+        T     => "t",
+        P     => "p",
+        H     => "h",
+    }
+}
+
+/// The romaja sequence that `read_vowel` reads as the given vowel.
+fn vowel_romaja(vow: Vowel) -> &'static str {
+    use hangeul2::Vowel::*;
+    match vow {
+        A   => "a",
+        Ae  => "ae",
+        Ya  => "ya",
+        Yae => "yae",
+        Eo  => "eo",
+        E   => "e",
+        Yeo => "yeo",
+        Ye  => "ye",
+        O   => "o",
+        Wa  => "wa",
+        Wae => "wae",
+        Oe  => "oe",
+        Yo  => "yo",
+        U   => "u",
+        Weo => "weo",
+        We  => "we",
+        Wi  => "wi",
+        Yu  => "yu",
+        Y   => "y",
+        Yi  => "yi",
+        I   => "i",
+    }
+}
+
+/// The romaja sequence that `read_final` reads as the given final.
+fn final_romaja(fin: Final) -> &'static str {
+    use hangeul2::Final::*;
+    match fin {
+        Empty => "",
+        G     => "g",
+        Gg    => "G",
+        Gs    => "gs",
+        N     => "n",
+        Nj    => "nj",
+        Nh    => "nh",
+        D     => "d",
+        L     => "l",
+        Lg    => "lg",
+        Lm    => "lm",
+        Lb    => "lb",
+        Ls    => "ls",
+        Lt    => "lt",
+        Lp    => "lp",
+        Lh    => "lh",
+        M     => "m",
+        B     => "b",
+        Bs    => "bs",
+        S     => "s",
+        Ss    => "S",
+        Ieung => "x",
+        J     => "j",
+        Ch    => "ch",
+        K     => "k",
+        T     => "t",
+        P     => "p",
+        H     => "h",
+    }
+}
+
+/// What the romaja written so far ends with.
+#[derive(Debug, Clone, Copy)]
+enum Previous {
+    /// Anything that is not a syllable (or the start of the text).
+    Other,
+    /// A syllable without a final consonant.
+    Vowel(Vowel),
+    /// A syllable with a final consonant.
+    Final,
+}
+
+/// Converts the 한글 syllables of the given text to romaja.
+///
+/// The result deromanizes back into the same text, as an explicit ieung
+/// (`x`) is written whenever the syllables would otherwise be read
+/// differently. Characters that are not 한글 syllables are left untouched,
+/// so this only holds for text without latin letters, which `deromanize`
+/// would read as romaja.
+///
+/// Ex: `안녕하세요` => `annyeoxhaseyo`
+pub fn romanize(text: &str) -> String {
+    let mut s = String::new();
+    romanize_into(text, &mut s);
+    s
+}
+
+/// Converts the 한글 syllables of the given text to romaja.
+/// See `romanize`.
+pub fn romanize_into(text: &str, s: &mut String) {
+    let mut prev = Previous::Other;
+    for ch in text.chars() {
+        let block = if let Some(block) = Block::from_char(ch) {
+            block
+        } else {
+            s.push(ch);
+            prev = Previous::Other;
+            continue;
+        };
+        let vowel = vowel_romaja(block.vowel);
+        if block.initial == Initial::Ieung {
+            let needs_ieung = match prev {
+                Previous::Other => false,
+                Previous::Final => true,
+                // The vowels must not be read as a single, longer vowel
+                Previous::Vowel(pvow) => {
+                    let pvowel = vowel_romaja(pvow);
+                    let joined = format!("{}{}", pvowel, vowel);
+                    read_vowel(&joined) != Some((pvow, pvowel.len()))
+                }
+            };
+            if needs_ieung {
+                s.push_str(initial_romaja(Initial::Ieung));
+            }
+        } else {
+            s.push_str(initial_romaja(block.initial));
+        }
+        s.push_str(vowel);
+        s.push_str(final_romaja(block.fin));
+        prev = if block.fin == Final::Empty {
+            Previous::Vowel(block.vowel)
+        } else {
+            Previous::Final
+        };
+    }
+}
+
+/// Converts the 한글 syllables of the given text using the given
+/// romanization system.
+///
+/// Ex: `romanize_with("한글", Scheme::McCuneReischauer)` => `han'gŭl`
+pub fn romanize_with(text: &str, scheme: Scheme) -> String {
+    let mut s = String::new();
+    romanize_with_into(text, scheme, &mut s);
+    s
+}
+
+/// Converts the 한글 syllables of the given text using the given
+/// romanization system. See `romanize_with`.
+pub fn romanize_with_into(text: &str, scheme: Scheme, s: &mut String) {
+    match scheme {
+        Scheme::Dero => romanize_into(text, s),
+        Scheme::Revised => revised::romanize_into(text, s),
+        Scheme::McCuneReischauer => mccune_reischauer::romanize_into(text, s),
+        Scheme::Yale => yale::romanize_into(text, s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hangeul2::Block;
+    use super::{romanize, romanize_with, Scheme};
+    use super::super::deromanize;
+
+    fn syllables() -> Vec<char> {
+        (0xAC00..0xD7A4).map(|code| ::std::char::from_u32(code).unwrap()).collect()
+    }
+
+    #[test]
+    fn examples() {
+        assert_eq!(romanize("안녕하세요"), "annyeoxhaseyo");
+        assert_eq!(romanize("한글"), "hangyl");
+        assert_eq!(romanize("레온씨"), "rexonSi");
+        assert_eq!(romanize("이거 뭐예요?"), "igeo mweoyeyo?");
+        assert_eq!(romanize("아에"), "axe");
+        assert_eq!(romanize("맛있어요"), "masxiSxeoyo");
+    }
+
+    #[test]
+    fn schemes() {
+        assert_eq!(romanize_with("한글", Scheme::Dero), "hangyl");
+        assert_eq!(romanize_with("한글", Scheme::Revised), "hangeul");
+        assert_eq!(romanize_with("한글", Scheme::McCuneReischauer), "han'gŭl");
+        assert_eq!(romanize_with("한글", Scheme::Yale), "hankul");
+    }
+
+    #[test]
+    fn round_trip_mixed_text() {
+        let text = "이거 뭐예요? 123, ㅋㅋ!";
+        assert_eq!(deromanize(&romanize(text)), text);
+        // Latin letters are not escaped
+        assert_eq!(romanize("한글 text"), "hangyl text");
+        assert_eq!(deromanize(&romanize("한글 text")), "한글 텡ㅌ");
+    }
+
+    #[test]
+    fn round_trip_single_syllables() {
+        for ch in syllables() {
+            let text = ch.to_string();
+            assert_eq!(deromanize(&romanize(&text)), text, "'{}'", romanize(&text));
+        }
+    }
+
+    #[test]
+    fn round_trip_syllable_pairs() {
+        // The romaja of a syllable only depends on its neighbour through its
+        // vowel and final, so one initial is enough for the first syllable.
+        let syllables = syllables();
+        let firsts = syllables.iter().filter(|ch| {
+            Block::from_char(**ch).unwrap().initial == ::hangeul2::Initial::G
+        });
+        let seconds: Vec<_> = syllables.iter().filter(|ch| {
+            Block::from_char(**ch).unwrap().fin == ::hangeul2::Final::Empty
+        }).collect();
+        for first in firsts {
+            for second in &seconds {
+                let text = format!("{}{}", first, second);
+                let romaja = romanize(&text);
+                assert_eq!(deromanize(&romaja), text, "'{}'", romaja);
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Sound changes between the syllables of a word, as needed by the
+//! romanization systems that follow the pronunciation.
+
+use hangeul2::{Initial, Vowel, Final, Block};
+
+use super::split_final;
+
+/// A part of a text: either a word of consecutive syllables or another
+/// character.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Piece {
+    Word(Vec<Block>),
+    Other(char),
+}
+
+/// Splits the given text into words of 한글 syllables and other characters.
+pub fn pieces(text: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut word = Vec::new();
+    for ch in text.chars() {
+        if let Some(block) = Block::from_char(ch) {
+            word.push(block);
+        } else {
+            if !word.is_empty() {
+                pieces.push(Piece::Word(word));
+                word = Vec::new();
+            }
+            pieces.push(Piece::Other(ch));
+        }
+    }
+    if !word.is_empty() {
+        pieces.push(Piece::Word(word));
+    }
+    pieces
+}
+
+/// Returns the final that the given final is pronounced as before a
+/// consonant or at the end of a word (one of ㄱ ㄴ ㄷ ㄹ ㅁ ㅂ ㅇ).
+pub fn neutralize(fin: Final) -> Final {
+    use hangeul2::Final::*;
+    match fin {
+        Empty => Empty,
+        G | Gg | Gs | Lg | K => G,
+        N | Nj | Nh => N,
+        D | S | Ss | J | Ch | T | H => D,
+        L | Lb | Ls | Lt | Lh => L,
+        M | Lm => M,
+        B | Bs | Lp | P => B,
+        Ieung => Ieung,
+    }
+}
+
+/// Returns whether the given syllable is the stem 밟, whose ㄼ is pronounced
+/// as ㅂ rather than ㄹ before a consonant (밟다 => 밥따).
+fn is_balb(block: &Block) -> bool {
+    block.initial == Initial::B && block.vowel == Vowel::A && block.fin == Final::Lb
+}
+
+/// Returns the aspirated counterpart of a plain or tense stop.
+fn aspirate(ini: Initial) -> Option<Initial> {
+    use hangeul2::Initial::*;
+    match ini {
+        G | Gg | K => Some(K),
+        D | Dd | T | S | Ss => Some(T),
+        B | Bb | P => Some(P),
+        J | Jj | Ch => Some(Ch),
+        _ => None,
+    }
+}
+
+/// Applies the sound changes at the boundary between two syllables.
+fn change(prev: &mut Block, next: &mut Block) {
+    use hangeul2::Initial::*;
+    let coda = prev.fin;
+    if coda == Final::Empty {
+        return;
+    }
+
+    // Liaison: the final moves on to a following vowel, except for ㅇ, and
+    // ㅎ which is dropped.
+    if next.initial == Ieung {
+        let (fin, ini) = match coda {
+            Final::Ieung => (Final::Ieung, Ieung),
+            Final::H => (Final::Empty, Ieung),
+            Final::Nh => (Final::Empty, N),
+            Final::Lh => (Final::Empty, R),
+            _ => split_final(coda),
+        };
+        // Palatalization: ㄷ and ㅌ before 이 become ㅈ and ㅊ
+        let ini = match (ini, next.vowel) {
+            (D, Vowel::I) => J,
+            (T, Vowel::I) => Ch,
+            (ini, _) => ini,
+        };
+        prev.fin = fin;
+        next.initial = ini;
+        return;
+    }
+
+    // Aspiration, from a ㅎ either before or after the stop.
+    let (coda, ini) = match (coda, next.initial) {
+        (Final::H, ini) | (Final::Nh, ini) | (Final::Lh, ini) => {
+            let rest = match coda {
+                Final::Nh => Final::N,
+                Final::Lh => Final::L,
+                _ => Final::Empty,
+            };
+            match ini {
+                G | D | J => (rest, aspirate(ini).unwrap()),
+                S => (rest, Ss),
+                N if rest == Final::Empty => (Final::N, N),
+                _ => (rest, ini),
+            }
+        }
+        (coda, H) => {
+            let (rest, last) = split_final(coda);
+            match aspirate(last) {
+                Some(ini) => (rest, ini),
+                None => (coda, H),
+            }
+        }
+        (coda, ini) => (coda, ini),
+    };
+    let ini = match (ini, next.vowel) {
+        (T, Vowel::I) => Ch,
+        (ini, _) => ini,
+    };
+
+    // ㄺ keeps its ㄹ before ㄱ (맑게 => 말께), and the ㄼ of 밟 is ㅂ
+    let coda = match (coda, ini) {
+        (Final::Lg, G) => Final::L,
+        (Final::Lb, _) if is_balb(prev) => Final::B,
+        (coda, _) => neutralize(coda),
+    };
+
+    // Nasalization and liquidization
+    let (coda, ini) = match (coda, ini) {
+        (Final::G, N) | (Final::G, M) => (Final::Ieung, ini),
+        (Final::D, N) | (Final::D, M) => (Final::N, ini),
+        (Final::B, N) | (Final::B, M) => (Final::M, ini),
+        (Final::G, R) => (Final::Ieung, N),
+        (Final::D, R) => (Final::N, N),
+        (Final::B, R) => (Final::M, N),
+        (Final::M, R) | (Final::Ieung, R) => (coda, N),
+        (Final::N, R) | (Final::L, N) => (Final::L, R),
+        (coda, ini) => (coda, ini),
+    };
+    prev.fin = coda;
+    next.initial = ini;
+}
+
+/// Changes the syllables of a word into the way they are pronounced.
+///
+/// The result has the final consonants moved or assimilated as they are
+/// pronounced (eg `신라` => `실라`, `같이` => `가치`), and every remaining
+/// final is one of ㄱ ㄴ ㄷ ㄹ ㅁ ㅂ ㅇ.
+pub fn pronounce_word(word: &mut [Block]) {
+    for i in 1..word.len() {
+        let (before, after) = word.split_at_mut(i);
+        change(&mut before[i - 1], &mut after[0]);
+    }
+    if let Some(last) = word.last_mut() {
+        last.fin = if is_balb(last) { Final::B } else { neutralize(last.fin) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hangeul2::Block;
+    use super::pronounce_word;
+
+    fn pronounce(text: &str) -> String {
+        let mut word: Vec<_> = text.chars().map(|ch| Block::from_char(ch).unwrap()).collect();
+        pronounce_word(&mut word);
+        word.iter().map(|block| block.combine()).collect()
+    }
+
+    #[test]
+    fn sound_changes() {
+        let examples = [
+            ("한글", "한글"),
+            ("신라", "실라"),
+            ("별내", "별래"),
+            ("종로", "종노"),
+            ("독립", "동닙"),
+            ("국물", "궁물"),
+            ("같이", "가치"),
+            ("굳히다", "구치다"),
+            ("좋고", "조코"),
+            ("좋아", "조아"),
+            ("않아", "아나"),
+            ("값이", "갑시"),
+            ("밖에", "바께"),
+            ("있는", "인는"),
+        ];
+        for &(word, expected) in &examples {
+            assert_eq!(pronounce(word), expected, "{}", word);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Deromanization of streams of text.
+//!
+//! The text is converted up to the last whitespace seen so far, as nothing
+//! after whitespace can change how the text before it is read. A final
+//! consonant at the end of one chunk can thus still move on to a vowel at
+//! the start of the next. The text after the last whitespace is kept until
+//! more whitespace (or the end) arrives, so the memory used grows with the
+//! longest run of text without whitespace, and a text without any is held
+//! entirely.
+//!
+//! This relies on the scheme never reading whitespace as part of a jamo,
+//! which holds for the built-in schemes and every `TableScheme`.
+
+use std::io::{self, BufRead, Read, Write};
+
+use super::{deromanize_with_into, ConversionScheme, InputScheme};
+
+/// Returns the length of the text that can be converted, which ends with
+/// the last whitespace in it.
+fn convertible_len(bytes: &[u8]) -> usize {
+    bytes.iter().rposition(|byte| byte.is_ascii_whitespace()).map_or(0, |pos| pos + 1)
+}
+
+/// Converts the given bytes, which must be UTF-8, and adds them to `out`.
+fn convert<S: ConversionScheme>(bytes: &[u8], scheme: &S, out: &mut Vec<u8>) -> io::Result<()> {
+    let text = ::std::str::from_utf8(bytes)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let mut s = String::new();
+    deromanize_with_into(text, scheme, &mut s);
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+/// A writer that deromanizes the text written to it, and writes the result to
+/// another writer.
+///
+/// The text at the end is only converted by `finish`, or when the writer is
+/// dropped (ignoring errors), as `flush` cannot know that no more text will
+/// follow it.
+pub struct DeroWriter<W: Write, S: ConversionScheme = InputScheme> {
+    inner: Option<W>,
+    scheme: S,
+    pending: Vec<u8>,
+    output: Vec<u8>,
+}
+
+impl<W: Write> DeroWriter<W> {
+    /// Creates a writer that reads the romaja of this library.
+    pub fn new(inner: W) -> DeroWriter<W> {
+        DeroWriter::with_scheme(inner, InputScheme::Dero)
+    }
+}
+
+impl<W: Write, S: ConversionScheme> DeroWriter<W, S> {
+    /// Creates a writer that reads romaja using the given scheme.
+    pub fn with_scheme(inner: W, scheme: S) -> DeroWriter<W, S> {
+        DeroWriter { inner: Some(inner), scheme, pending: Vec::new(), output: Vec::new() }
+    }
+
+    /// Converts and writes the rest of the text, and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_pending(self.pending.len())?;
+        let mut inner = self.inner.take().unwrap();
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    /// Converts and writes the first `len` bytes of the pending text, which
+    /// are only removed once they are written.
+    fn write_pending(&mut self, len: usize) -> io::Result<()> {
+        if len == 0 {
+            return Ok(());
+        }
+        self.output.clear();
+        convert(&self.pending[..len], &self.scheme, &mut self.output)?;
+        self.inner.as_mut().unwrap().write_all(&self.output)?;
+        self.pending.drain(..len);
+        Ok(())
+    }
+}
+
+impl<W: Write, S: ConversionScheme> Write for DeroWriter<W, S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let start = self.pending.len();
+        self.pending.extend_from_slice(buf);
+        let len = convertible_len(&self.pending[start..]);
+        if len > 0 {
+            if let Err(err) = self.write_pending(start + len) {
+                // None of the buffer is taken when it could not be written
+                self.pending.truncate(start);
+                return Err(err);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write, S: ConversionScheme> Drop for DeroWriter<W, S> {
+    fn drop(&mut self) {
+        if self.inner.is_some() && !::std::thread::panicking() {
+            let len = self.pending.len();
+            let _ = self.write_pending(len);
+        }
+    }
+}
+
+/// A reader of the deromanized text of another reader. See
+/// `deromanize_reader`.
+pub struct DeroReader<R: BufRead, S: ConversionScheme = InputScheme> {
+    inner: R,
+    scheme: S,
+    pending: Vec<u8>,
+    output: Vec<u8>,
+    pos: usize,
+}
+
+/// Returns a reader of the deromanized text of the given reader.
+pub fn deromanize_reader<R: BufRead>(reader: R) -> DeroReader<R> {
+    deromanize_reader_with(reader, InputScheme::Dero)
+}
+
+/// Returns a reader of the text of the given reader, deromanized using the
+/// given scheme. See `deromanize_reader`.
+pub fn deromanize_reader_with<R: BufRead, S: ConversionScheme>(reader: R, scheme: S) -> DeroReader<R, S> {
+    DeroReader { inner: reader, scheme, pending: Vec::new(), output: Vec::new(), pos: 0 }
+}
+
+impl<R: BufRead, S: ConversionScheme> DeroReader<R, S> {
+    /// Reads and converts text until some output is ready, or the end of the
+    /// text is reached.
+    fn convert_more(&mut self) -> io::Result<()> {
+        self.output.clear();
+        self.pos = 0;
+        while self.output.is_empty() {
+            let (used, len) = {
+                let available = self.inner.fill_buf()?;
+                if available.is_empty() {
+                    let pending = ::std::mem::take(&mut self.pending);
+                    return convert(&pending, &self.scheme, &mut self.output);
+                }
+                let len = convertible_len(available);
+                let used = if len > 0 { len } else { available.len() };
+                self.pending.extend_from_slice(&available[..used]);
+                (used, len)
+            };
+            self.inner.consume(used);
+            if len > 0 {
+                let pending = ::std::mem::take(&mut self.pending);
+                convert(&pending, &self.scheme, &mut self.output)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead, S: ConversionScheme> Read for DeroReader<R, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = {
+            let available = self.fill_buf()?;
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            len
+        };
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead, S: ConversionScheme> BufRead for DeroReader<R, S> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos >= self.output.len() {
+            self.convert_more()?;
+        }
+        Ok(&self.output[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.output.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use super::{deromanize_reader, deromanize_reader_with, DeroWriter};
+    use super::super::{deromanize, deromanize_with, InputScheme};
+
+    const TEXT: &str = "annyeoxhaseyo, jeonyn dal\ngi meogeo!\r\n\
+        igeo mweoyeyo? 한글 masxiSxeoyo\tsal\n\nkkk";
+
+    #[test]
+    fn writer_matches_deromanize() {
+        for size in 1..TEXT.len() + 1 {
+            let mut writer = DeroWriter::new(Vec::new());
+            for chunk in TEXT.as_bytes().chunks(size) {
+                writer.write_all(chunk).unwrap();
+            }
+            let output = writer.finish().unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), deromanize(TEXT), "size {}", size);
+        }
+    }
+
+    #[test]
+    fn writer_converts_on_drop() {
+        let mut output = Vec::new();
+        {
+            let mut writer = DeroWriter::with_scheme(&mut output, InputScheme::Revised);
+            write!(writer, "hang").unwrap();
+            write!(writer, "eul").unwrap();
+        }
+        assert_eq!(String::from_utf8(output).unwrap(), "한글");
+    }
+
+    #[test]
+    fn reader_matches_deromanize() {
+        for size in 1..TEXT.len() + 1 {
+            let mut output = String::new();
+            let reader = BufReader::with_capacity(size, TEXT.as_bytes());
+            deromanize_reader(reader).read_to_string(&mut output).unwrap();
+            assert_eq!(output, deromanize(TEXT), "size {}", size);
+        }
+    }
+
+    #[test]
+    fn reader_lines() {
+        let reader = BufReader::with_capacity(1, "hangeul\nhang-eul".as_bytes());
+        let lines: Vec<_> = deromanize_reader_with(reader, InputScheme::Revised)
+            .lines().map(Result::unwrap).collect();
+        assert_eq!(lines, vec![
+            deromanize_with("hangeul", InputScheme::Revised),
+            deromanize_with("hang-eul", InputScheme::Revised),
+        ]);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let mut writer = DeroWriter::new(Vec::new());
+        writer.write_all(b"ga\xff \n").unwrap_err();
+        writer.write_all(b"na \n").unwrap();
+        assert_eq!(writer.finish().unwrap(), "나 \n".as_bytes());
+        let mut output = Vec::new();
+        let bytes: &[u8] = b"ga \xffna";
+        assert!(deromanize_reader(bytes).read_to_end(&mut output).is_err());
+    }
+}
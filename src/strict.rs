@@ -0,0 +1,180 @@
+//! Deromanization that rejects text it cannot fully convert.
+//!
+//! `deromanize` passes letters it cannot read through as they are, and
+//! writes an initial consonant without a vowel as a lone jamo (`kkk` =>
+//! `ㅋㅋㅋ`). The functions here report those places as mistakes instead.
+//! Anything that is not an ASCII letter, such as spaces, punctuation and
+//! 한글, is still passed through.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+use hangeul2::{Initial, Final, Block};
+
+use super::{deromanize_with_spans, ConversionScheme, InputScheme};
+
+/// Why a part of the text could not be converted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reason {
+    /// A letter that does not start any jamo, such as a stray `c`.
+    Unknown(char),
+    /// A letter that only starts a vowel together with the letters after
+    /// it, such as a `w` that is not followed by a vowel.
+    IncompleteVowel(char),
+    /// An initial consonant that is not followed by a vowel.
+    MissingVowel(Initial),
+    /// A consonant where a final was expected, that cannot be a final
+    /// consonant (ㄸ, ㅃ and ㅉ).
+    NotAFinal(Initial),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Reason::Unknown(ch) => write!(f, "'{}' could not be read", ch),
+            Reason::IncompleteVowel(ch) => write!(f, "'{}' is not followed by a vowel", ch),
+            Reason::MissingVowel(ini) => {
+                write!(f, "{} is not followed by a vowel", ini.as_char())
+            }
+            Reason::NotAFinal(ini) => {
+                write!(f, "{} cannot be a final consonant", ini.as_char())
+            }
+        }
+    }
+}
+
+/// A part of the text that could not be converted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mistake {
+    /// The bytes of the text that the mistake is about.
+    pub span: Range<usize>,
+    pub reason: Reason,
+}
+
+impl fmt::Display for Mistake {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bytes {}..{}: {}", self.span.start, self.span.end, self.reason)
+    }
+}
+
+/// An error from deromanizing text strictly, listing every mistake in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeroError {
+    pub mistakes: Vec<Mistake>,
+}
+
+impl fmt::Display for DeroError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not convert the text:")?;
+        for mistake in &self.mistakes {
+            write!(f, "\n  {}", mistake)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for DeroError {}
+
+/// Returns whether the given letter starts a longer vowel.
+fn starts_vowel<S: ConversionScheme>(ch: char, scheme: &S) -> bool {
+    "aeiouy".chars().any(|next| {
+        let text = format!("{}{}", ch, next);
+        scheme.read_vowel(&text).is_some_and(|(_, len)| len == text.len())
+    })
+}
+
+/// Converts the given romaja to 한글, or reports every part of it that could
+/// not be converted.
+///
+/// Ex: `hangyl` => `Ok("한글")`, `kkk` => `Err(..)`
+pub fn deromanize_strict(text: &str) -> Result<String, DeroError> {
+    deromanize_strict_with(text, InputScheme::Dero)
+}
+
+/// Converts the given text to 한글, reading it as the given romaja scheme,
+/// or reports every part of it that could not be converted.
+/// See `deromanize_strict`.
+pub fn deromanize_strict_with<S: ConversionScheme>(text: &str, scheme: S) -> Result<String, DeroError> {
+    let mut s = String::new();
+    let mut mistakes = Vec::new();
+    // The end of the last syllable without a final consonant
+    let mut open_end = None;
+    for (ch, span) in deromanize_with_spans(text, &scheme) {
+        s.push(ch);
+        let source = &text[span.clone()];
+        let reason = if let Some(block) = Block::from_char(ch) {
+            open_end = if block.fin == Final::Empty { Some(span.end) } else { None };
+            continue;
+        } else if source.chars().eq(Some(ch)) {
+            if !ch.is_ascii_alphabetic() {
+                None
+            } else if starts_vowel(ch, &scheme) {
+                Some(Reason::IncompleteVowel(ch))
+            } else {
+                Some(Reason::Unknown(ch))
+            }
+        } else if let Some(ini) = Initial::from_char(ch) {
+            if open_end == Some(span.start) && scheme.read_final(source).is_none() {
+                Some(Reason::NotAFinal(ini))
+            } else {
+                Some(Reason::MissingVowel(ini))
+            }
+        } else {
+            None
+        };
+        open_end = None;
+        if let Some(reason) = reason {
+            mistakes.push(Mistake { span, reason });
+        }
+    }
+    if mistakes.is_empty() {
+        Ok(s)
+    } else {
+        Err(DeroError { mistakes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hangeul2::Initial;
+    use super::{deromanize_strict, deromanize_strict_with, Reason};
+    use super::super::InputScheme;
+
+    fn reasons(text: &str) -> Vec<(usize, Reason)> {
+        deromanize_strict(text).unwrap_err().mistakes.into_iter()
+            .map(|mistake| (mistake.span.start, mistake.reason))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_complete_text() {
+        assert_eq!(deromanize_strict("annyeoxhaseyo, hangyl!").unwrap(), "안녕하세요, 한글!");
+        assert_eq!(deromanize_strict("baBa 123 ㅋ").unwrap(), "바빠 123 ㅋ");
+        assert_eq!(deromanize_strict_with("hangeul", InputScheme::Revised).unwrap(), "한글");
+    }
+
+    #[test]
+    fn reports_mistakes() {
+        assert_eq!(reasons("kkk"), vec![
+            (0, Reason::MissingVowel(Initial::K)),
+            (1, Reason::MissingVowel(Initial::K)),
+            (2, Reason::MissingVowel(Initial::K)),
+        ]);
+        assert_eq!(reasons("gw"), vec![
+            (0, Reason::MissingVowel(Initial::G)),
+            (1, Reason::IncompleteVowel('w')),
+        ]);
+        assert_eq!(reasons("baB"), vec![(2, Reason::NotAFinal(Initial::Bb))]);
+        assert_eq!(reasons("cat"), vec![(0, Reason::Unknown('c'))]);
+        assert_eq!(reasons("jeo k"), vec![(4, Reason::MissingVowel(Initial::K))]);
+    }
+
+    #[test]
+    fn error_message() {
+        let err = deromanize_strict("gaD c").unwrap_err();
+        assert_eq!(err.to_string(), "Could not convert the text:\
+            \n  bytes 2..3: ㄸ cannot be a final consonant\
+            \n  bytes 4..5: 'c' could not be read");
+    }
+}
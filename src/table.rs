@@ -0,0 +1,501 @@
+//! Romaja schemes described by a mapping file.
+//!
+//! The file uses a small subset of TOML, with a section for each kind of
+//! jamo, and the names of the `hangeul2` enum variants as keys. A jamo can be
+//! written with several sequences by giving a list:
+//!
+//! ```toml
+//! # Our team layout
+//! [initials]
+//! G = "g"
+//! Gg = ["G", "q"]
+//! R = ["r", "l"]
+//! # ...
+//!
+//! [vowels]
+//! A = "a"
+//! # ...
+//!
+//! [finals]
+//! Lg = "lg"
+//! Ieung = "ng"
+//! # ...
+//! ```
+//!
+//! Every initial, vowel and (non-empty) final must be given. Sequences that
+//! are used for two jamo of the same kind, or where a consonant and a vowel
+//! would start with the same sequence, are reported as errors, as are
+//! sequences that contain whitespace, which always separates words.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use hangeul2::{Initial, Vowel, Final};
+
+use super::ConversionScheme;
+
+/// A problem found in a scheme file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// The line of the problem (starting from 1), if it is about a line.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// An error from loading a scheme file.
+#[derive(Debug)]
+pub enum SchemeError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file was read, but describes an invalid scheme.
+    Invalid(Vec<Problem>),
+}
+
+impl fmt::Display for SchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SchemeError::Io(ref err) => write!(f, "Could not read scheme: {}", err),
+            SchemeError::Invalid(ref problems) => {
+                write!(f, "Invalid scheme:")?;
+                for problem in problems {
+                    write!(f, "\n  {}", problem)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for SchemeError {}
+
+impl From<io::Error> for SchemeError {
+    fn from(err: io::Error) -> SchemeError {
+        SchemeError::Io(err)
+    }
+}
+
+/// A sequence for a jamo, and the line it was given on.
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    sequence: String,
+    jamo: T,
+    line: usize,
+}
+
+/// A romaja scheme read from a mapping file (see the module documentation).
+#[derive(Debug, Clone)]
+pub struct TableScheme {
+    initials: Vec<Entry<Initial>>,
+    vowels: Vec<Entry<Vowel>>,
+    finals: Vec<Entry<Final>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    Initials,
+    Vowels,
+    Finals,
+}
+
+fn initials() -> Vec<Initial> {
+    (0..).map(Initial::from_offset).take_while(Option::is_some).map(Option::unwrap).collect()
+}
+
+fn vowels() -> Vec<Vowel> {
+    (0..).map(Vowel::from_offset).take_while(Option::is_some).map(Option::unwrap).collect()
+}
+
+fn finals() -> Vec<Final> {
+    (1..).map(Final::from_offset).take_while(Option::is_some).map(Option::unwrap).collect()
+}
+
+/// Finds the jamo with the given variant name.
+fn find<T: fmt::Debug + Copy>(jamo: &[T], name: &str) -> Option<T> {
+    jamo.iter().cloned().find(|jamo| format!("{:?}", jamo) == name)
+}
+
+/// Parses a basic TOML string at the start of the text, and returns it along
+/// with the rest of the text.
+fn parse_string(text: &str) -> Result<(String, &str), String> {
+    let mut chars = text.char_indices();
+    match chars.next() {
+        Some((_, '"')) => {}
+        _ => return Err(format!("expected a string, found '{}'", text)),
+    }
+    let mut value = String::new();
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '"' => return Ok((value, &text[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, '"')) => value.push('"'),
+                Some((_, '\\')) => value.push('\\'),
+                Some((_, other)) => return Err(format!("unsupported escape '\\{}'", other)),
+                None => break,
+            },
+            ch => value.push(ch),
+        }
+    }
+    Err(String::from("unterminated string"))
+}
+
+/// Parses the value of an entry: a string or a list of strings.
+fn parse_value(text: &str) -> Result<Vec<String>, String> {
+    let text = text.trim();
+    let (values, rest) = if let Some(mut rest) = text.strip_prefix('[') {
+        let mut values = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                break (values, after);
+            }
+            let (value, after) = parse_string(rest)?;
+            values.push(value);
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                return Err(String::from("expected ',' or ']' in list"));
+            }
+        }
+    } else {
+        let (value, rest) = parse_string(text)?;
+        (vec![value], rest)
+    };
+    let rest = rest.trim();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(format!("unexpected '{}' after value", rest));
+    }
+    Ok(values)
+}
+
+/// Reports sequences that are given twice within a table.
+fn check_duplicates<T: fmt::Debug>(entries: &[Entry<T>], kind: &str, problems: &mut Vec<Problem>) {
+    for (i, entry) in entries.iter().enumerate() {
+        if let Some(first) = entries[..i].iter().find(|other| other.sequence == entry.sequence) {
+            problems.push(Problem {
+                line: Some(entry.line),
+                message: format!("ambiguous {} sequence '{}': used for both {:?} (line {}) and {:?}",
+                                 kind, entry.sequence, first.jamo, first.line, entry.jamo),
+            });
+        }
+    }
+}
+
+/// Reports consonant sequences that start the same way as vowel sequences,
+/// as the state machine could not tell those apart.
+fn check_conflicts<T: fmt::Debug>(consonants: &[Entry<T>], vowels: &[Entry<Vowel>], problems: &mut Vec<Problem>) {
+    for consonant in consonants {
+        for vowel in vowels {
+            let (a, b) = (&consonant.sequence, &vowel.sequence);
+            if a.starts_with(b.as_str()) || b.starts_with(a.as_str()) {
+                let (earlier, later) = if consonant.line < vowel.line {
+                    (consonant.line, vowel.line)
+                } else {
+                    (vowel.line, consonant.line)
+                };
+                problems.push(Problem {
+                    line: Some(later),
+                    message: format!("prefix conflict: consonant {:?} '{}' and vowel {:?} '{}' (line {})",
+                                     consonant.jamo, a, vowel.jamo, b, earlier),
+                });
+            }
+        }
+    }
+}
+
+/// Reports the jamo that have no sequence.
+fn check_missing<T: fmt::Debug + PartialEq>(all: &[T], entries: &[Entry<T>], kind: &str, problems: &mut Vec<Problem>) {
+    for jamo in all {
+        if !entries.iter().any(|entry| entry.jamo == *jamo) {
+            problems.push(Problem {
+                line: None,
+                message: format!("no sequence given for {} {:?}", kind, jamo),
+            });
+        }
+    }
+}
+
+/// Reads the longest sequence of the table that the text starts with.
+fn read_longest<T: Copy>(entries: &[Entry<T>], text: &str) -> Option<(T, usize)> {
+    entries.iter()
+        .filter(|entry| text.starts_with(entry.sequence.as_str()))
+        .max_by_key(|entry| entry.sequence.len())
+        .map(|entry| (entry.jamo, entry.sequence.len()))
+}
+
+impl TableScheme {
+    /// Parses and validates a scheme from the contents of a mapping file.
+    pub fn parse(text: &str) -> Result<TableScheme, SchemeError> {
+        let (all_initials, all_vowels, all_finals) = (initials(), vowels(), finals());
+        let mut scheme = TableScheme {
+            initials: Vec::new(),
+            vowels: Vec::new(),
+            finals: Vec::new(),
+        };
+        let mut problems = Vec::new();
+        let mut section = None;
+        for (i, line) in text.lines().enumerate() {
+            let lineno = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut problem = |message: String| {
+                problems.push(Problem { line: Some(lineno), message });
+            };
+            if line.starts_with('[') {
+                let name = line.split('#').next().unwrap().trim();
+                section = match name {
+                    "[initials]" => Some(Section::Initials),
+                    "[vowels]" => Some(Section::Vowels),
+                    "[finals]" => Some(Section::Finals),
+                    _ => {
+                        problem(format!("unknown section '{}'", name));
+                        None
+                    }
+                };
+                continue;
+            }
+            let (name, value) = match line.find('=') {
+                Some(index) => (line[..index].trim(), &line[index + 1..]),
+                None => {
+                    problem(format!("expected 'Name = \"sequence\"', found '{}'", line));
+                    continue;
+                }
+            };
+            let sequences = match parse_value(value) {
+                Ok(sequences) => sequences,
+                Err(message) => {
+                    problem(message);
+                    continue;
+                }
+            };
+            if sequences.iter().any(String::is_empty) {
+                problem(format!("empty sequence for {}", name));
+                continue;
+            }
+            if sequences.iter().any(|sequence| sequence.contains(char::is_whitespace)) {
+                problem(format!("sequence for {} contains whitespace", name));
+                continue;
+            }
+            match section {
+                Some(Section::Initials) => match find(&all_initials, name) {
+                    Some(jamo) => for sequence in sequences {
+                        scheme.initials.push(Entry { sequence, jamo, line: lineno });
+                    },
+                    None => problem(format!("unknown initial '{}'", name)),
+                },
+                Some(Section::Vowels) => match find(&all_vowels, name) {
+                    Some(jamo) => for sequence in sequences {
+                        scheme.vowels.push(Entry { sequence, jamo, line: lineno });
+                    },
+                    None => problem(format!("unknown vowel '{}'", name)),
+                },
+                Some(Section::Finals) => match find(&all_finals, name) {
+                    Some(jamo) => for sequence in sequences {
+                        scheme.finals.push(Entry { sequence, jamo, line: lineno });
+                    },
+                    None => problem(format!("unknown final '{}'", name)),
+                },
+                None => problem(format!("'{}' is not in an [initials], [vowels] or [finals] section", name)),
+            }
+        }
+        check_duplicates(&scheme.initials, "initial", &mut problems);
+        check_duplicates(&scheme.vowels, "vowel", &mut problems);
+        check_duplicates(&scheme.finals, "final", &mut problems);
+        check_conflicts(&scheme.initials, &scheme.vowels, &mut problems);
+        check_conflicts(&scheme.finals, &scheme.vowels, &mut problems);
+        problems.sort_by_key(|problem| problem.line.unwrap_or(0));
+        check_missing(&all_initials, &scheme.initials, "initial", &mut problems);
+        check_missing(&all_vowels, &scheme.vowels, "vowel", &mut problems);
+        check_missing(&all_finals, &scheme.finals, "final", &mut problems);
+        if problems.is_empty() {
+            Ok(scheme)
+        } else {
+            Err(SchemeError::Invalid(problems))
+        }
+    }
+
+    /// Reads, parses and validates a scheme from a mapping file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<TableScheme, SchemeError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        TableScheme::parse(&text)
+    }
+}
+
+impl ConversionScheme for TableScheme {
+    fn read_vowel(&self, text: &str) -> Option<(Vowel, usize)> {
+        read_longest(&self.vowels, text)
+    }
+
+    fn read_initial(&self, text: &str) -> Option<(Initial, usize)> {
+        read_longest(&self.initials, text)
+    }
+
+    fn read_final(&self, text: &str) -> Option<(Final, usize)> {
+        read_longest(&self.finals, text)
+    }
+
+    fn separator(&self) -> &str {
+        self.initials.iter()
+            .find(|entry| entry.jamo == Initial::Ieung)
+            .map_or("x", |entry| &entry.sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TableScheme, SchemeError, Problem};
+    use super::super::{deromanize, deromanize_with};
+
+    /// The romaja of the crate documentation as a scheme file.
+    const DERO: &str = r#"
+# The default dero scheme
+[initials]
+G = "g"
+Gg = "G"
+N = "n"
+D = "d"
+Dd = "D"
+R = ["r", "l"]
+M = "m"
+B = "b"
+Bb = "B"
+S = "s"
+Ss = "S"
+Ieung = "x"
+J = "j"
+Jj = "J"
+Ch = "ch"
+K = "k"
+T = "t"
+P = "p"
+H = "h"
+
+[vowels]
+A = "a"
+Ae = "ae"
+Ya = "ya"
+Yae = "yae"
+Eo = "eo"
+E = "e"
+Yeo = "yeo"
+Ye = "ye"
+O = "o"
+Wa = "wa"
+Wae = "wae"
+Oe = "oe"
+Yo = "yo"
+U = "u"
+Weo = "weo"
+We = "we"
+Wi = "wi"
+Yu = "yu"
+Y = "y"
+Yi = "yi"
+I = "i"
+
+[finals]
+G = "g"
+Gg = "G"
+Gs = "gs"
+N = "n"
+Nj = "nj"
+Nh = "nh"
+D = "d"
+L = ["l", "r"]
+Lg = ["lg", "rg"]
+Lm = ["lm", "rm"]
+Lb = ["lb", "rb"]
+Ls = ["ls", "rs"]
+Lt = ["lt", "rt"]
+Lp = ["lp", "rp"]
+Lh = ["lh", "rh"]
+M = "m"
+B = "b"
+Bs = "bs"
+S = "s"
+Ss = "S"
+Ieung = "x" # ng is only used for initials
+J = "j"
+Ch = "ch"
+K = "k"
+T = "t"
+P = "p"
+H = "h"
+"#;
+
+    fn problems(text: &str) -> Vec<Problem> {
+        match TableScheme::parse(text) {
+            Err(SchemeError::Invalid(problems)) => problems,
+            other => panic!("Expected an invalid scheme, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn dero_table_matches_default() {
+        let scheme = TableScheme::parse(DERO).unwrap();
+        let text = "annyeoxhaseyo, jinJa kkk! igeo mweoyeyo? rexonSi balgda eobsxeoyo";
+        assert_eq!(deromanize_with(text, &scheme), deromanize(text));
+    }
+
+    #[test]
+    fn custom_layout() {
+        let text = DERO.replace("Gg = \"G\"", "Gg = [\"G\", \"q\"]")
+            .replace("Ieung = \"x\" #", "Ieung = \"ng\" #");
+        let scheme = TableScheme::parse(&text).unwrap();
+        assert_eq!(deromanize_with("qachi", &scheme), "까치");
+        assert_eq!(deromanize_with("sarang", &scheme), "사랑");
+    }
+
+    #[test]
+    fn reports_ambiguous_sequences() {
+        let text = DERO.replace("Gg = \"G\"\nN", "Gg = \"g\"\nN");
+        let problems = problems(&text);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(5));
+        assert!(problems[0].message.contains("'g'"), "{}", problems[0].message);
+    }
+
+    #[test]
+    fn reports_prefix_conflicts() {
+        // Conflicts with 'wa', 'wae', 'weo', 'we' and 'wi'
+        let text = DERO.replacen("K = \"k\"", "K = \"w\"", 1);
+        let problems = problems(&text);
+        assert_eq!(problems.iter().map(|p| p.line).collect::<Vec<_>>(),
+                   vec![Some(34), Some(35), Some(39), Some(40), Some(41)]);
+        assert!(problems.iter().all(|p| p.message.contains("(line 19)")));
+    }
+
+    #[test]
+    fn reports_whitespace_in_sequences() {
+        let text = DERO.replace("Gg = \"G\"\nN", "Gg = \"g g\"\nN");
+        let problems = problems(&text);
+        // Gg is then missing as well
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].line, Some(5));
+        assert!(problems[0].message.contains("whitespace"), "{}", problems[0].message);
+    }
+
+    #[test]
+    fn reports_syntax_and_missing_entries() {
+        let problems = problems("[initials]\nG = g\nQ = \"q\"\n[consonants]\n");
+        let lines: Vec<_> = problems.iter().map(|p| p.line).collect();
+        assert_eq!(&lines[..3], &[Some(2), Some(3), Some(4)]);
+        assert!(lines[3..].iter().all(Option::is_none));
+        assert_eq!(problems.len(), 3 + 19 + 21 + 27);
+    }
+}
@@ -7,7 +7,9 @@ use termion::raw::IntoRawMode;
 use std::collections::VecDeque;
 use std::io::{stdin, stdout, Write};
 
-use super::deromanize_escaped;
+use super::{ambiguities_with, deromanize_with_spans, AmbiguityKind, Composer, ConversionScheme};
+use super::{EscapeConfig, InputScheme};
+use super::escape::{Escaper, Token};
 
 pub struct History {
     max_len: usize,
@@ -62,7 +64,180 @@ impl History {
 
 const DEFAULT_HISTORY_SIZE: usize = 64;
 
+/// The number of columns that the given text takes up in a terminal.
+fn width(text: &str) -> usize {
+    text.chars().map(|ch| match ch {
+        '\u{1100}'..='\u{115F}' | '\u{2E80}'..='\u{A4CF}' | '\u{AC00}'..='\u{D7A3}'
+            | '\u{F900}'..='\u{FAFF}' | '\u{FF00}'..='\u{FF60}' | '\u{FFE0}'..='\u{FFE6}' => 2,
+        _ => 1,
+    }).sum()
+}
+
+/// The line being edited, both as typed and as converted so far.
+/// Escaped text is not converted, or when the line is marked, only the text
+/// between the delimiters is.
+///
+/// Text is typed at the cursor, which is moved by the characters that the
+/// line is converted into.
+struct Line<'a, S: 'a> {
+    text: String,
+    /// Where text is typed, as a byte offset of the typed text.
+    cursor: usize,
+    scheme: &'a S,
+    composer: Composer<&'a S>,
+    escapes: &'a EscapeConfig,
+    marked: bool,
+    escaper: Escaper<'a>,
+    tokens: Vec<Token>,
+}
+
+impl<'a, S: ConversionScheme> Line<'a, S> {
+    fn new(scheme: &'a S, escapes: &'a EscapeConfig, marked: bool) -> Line<'a, S> {
+        Line {
+            text: String::new(),
+            cursor: 0,
+            scheme,
+            composer: Composer::new(scheme),
+            escapes,
+            marked,
+            escaper: Escaper::new(escapes),
+            tokens: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        if self.cursor < self.text.len() {
+            let mut text = self.text.clone();
+            text.insert(self.cursor, ch);
+            let cursor = self.cursor + ch.len_utf8();
+            self.set(&text);
+            self.cursor = cursor;
+            return;
+        }
+        self.text.push(ch);
+        self.cursor = self.text.len();
+        self.escaper.push(ch, &mut self.tokens);
+        self.apply_tokens();
+    }
+
+    fn apply_tokens(&mut self) {
+        for token in self.tokens.drain(..) {
+            match token {
+                Token::Outside(ch) if !self.marked => self.composer.push(ch),
+                Token::Inside(ch) if self.marked => self.composer.push(ch),
+                Token::Outside(ch) | Token::Inside(ch) | Token::Escaped(ch) => {
+                    self.composer.push_literal(ch.encode_utf8(&mut [0; 4]));
+                }
+                Token::Delimiter => self.composer.commit(),
+            }
+        }
+    }
+
+    /// Replaces the line with the given text.
+    fn set(&mut self, text: &str) {
+        self.text.clear();
+        self.cursor = 0;
+        self.composer.clear();
+        self.escaper = Escaper::new(self.escapes);
+        for ch in text.chars() {
+            self.push(ch);
+        }
+    }
+
+    /// Removes the last jamo being composed, or else the last character.
+    /// Before the end of the line, the character before the cursor is removed.
+    fn backspace(&mut self) {
+        if self.cursor < self.text.len() {
+            if let Some((before, _)) = self.text[..self.cursor].char_indices().last() {
+                let mut text = self.text.clone();
+                text.remove(before);
+                self.set(&text);
+                self.cursor = before;
+            }
+        } else if self.escaper.is_idle() && !self.composer.input().is_empty() {
+            if let Some(removed) = self.composer.backspace() {
+                let len = self.text.len() - removed.len();
+                self.text.truncate(len);
+                self.cursor = len;
+            }
+        } else {
+            let mut text = self.text.clone();
+            text.pop();
+            self.set(&text);
+        }
+    }
+
+    /// The places that the cursor can be moved to: the start of every
+    /// character that the typed text is read as, and the end of the line.
+    fn cursor_stops(&self) -> Vec<usize> {
+        let mut stops: Vec<_> = deromanize_with_spans(&self.text, self.scheme).into_iter()
+            .map(|(_, span)| span.start)
+            .collect();
+        stops.push(self.text.len());
+        stops
+    }
+
+    /// Moves the cursor back by one converted character.
+    fn left(&mut self) {
+        let cursor = self.cursor;
+        self.cursor = self.cursor_stops().into_iter().rev().find(|&stop| stop < cursor).unwrap_or(0);
+    }
+
+    /// Moves the cursor forward by one converted character.
+    fn right(&mut self) {
+        let cursor = self.cursor;
+        self.cursor = self.cursor_stops().into_iter().find(|&stop| stop > cursor).unwrap_or(cursor);
+    }
+
+    /// The number of columns of the converted line before the cursor.
+    fn cursor_column(&self) -> usize {
+        if self.cursor == self.text.len() {
+            return width(&self.hangeul());
+        }
+        let mut before = Line::new(self.scheme, self.escapes, self.marked);
+        before.set(&self.text[..self.cursor]);
+        width(&before.hangeul())
+    }
+
+    /// A question for every final consonant that might have been meant to
+    /// stay in its syllable.
+    fn warning(&self) -> String {
+        if self.marked || self.text.contains(&self.escapes.open[..]) {
+            return String::new();
+        }
+        ambiguities_with(&self.text, self.scheme).into_iter()
+            .filter(|ambiguity| ambiguity.kind == AmbiguityKind::MovedFinal)
+            .map(|ambiguity| format!("Did you mean {} ({})? ", ambiguity.hangeul, ambiguity.romaja))
+            .collect()
+    }
+
+    /// The converted line, with any characters that might still start a
+    /// delimiter as they are.
+    fn hangeul(&self) -> String {
+        format!("{}{}{}", self.composer.committed(), self.composer.preedit(), self.escaper.pending())
+    }
+
+    /// Finishes the line and returns it converted.
+    fn finish(&mut self) -> String {
+        self.escaper.finish(&mut self.tokens);
+        self.apply_tokens();
+        self.composer.take()
+    }
+}
+
 pub fn start_interactive_loop<F: FnMut(&str)>(initial_message: &str, prompt: &str, on_deromanize: &mut F) {
+    let escapes = EscapeConfig::default();
+    start_interactive_loop_with(initial_message, prompt, InputScheme::Dero, &escapes, false, on_deromanize)
+}
+
+/// Starts the interactive loop, reading romaja using the given scheme and
+/// leaving the text escaped as described by the config untouched. When
+/// `marked` is set, only the text between the delimiters is converted
+/// instead.
+pub fn start_interactive_loop_with<S, F>(initial_message: &str, prompt: &str, scheme: S,
+                                         escapes: &EscapeConfig, marked: bool, on_deromanize: &mut F)
+    where S: ConversionScheme, F: FnMut(&str)
+{
     let stdin = stdin();
     let mut stdout = stdout().into_raw_mode().unwrap();
     
@@ -86,33 +261,38 @@ pub fn start_interactive_loop<F: FnMut(&str)>(initial_message: &str, prompt: &st
     
     stdout.flush().unwrap();
     
-    let mut text = String::new();
-    let mut pos = 0;
+    let mut line = Line::new(&scheme, escapes, marked);
+    let mut warning = String::new();
+    // The text that was last warned about
+    let mut warned: Option<String> = None;
     let mut history = History::new(DEFAULT_HISTORY_SIZE);
     let mut history_index = 0;
     
     for c in stdin.keys() {
         match c.unwrap() {
             Key::Char('\n') => {
-                if text == "" {
+                if line.text.is_empty() {
                     continue;
                 }
                 
-                let hangeul = deromanize_escaped(&text);
-                
-                on_deromanize(&hangeul);
-                
-                history.push(&text);
-                history_index = history.len();
-                
-                text.clear();
-                pos = text.len();
+                // Ambiguous text is only saved when Enter is pressed again
+                let question = line.warning();
+                if !question.is_empty() && warned.as_ref() != Some(&line.text) {
+                    warning = format!("{}(Enter to keep it)", question);
+                    warned = Some(line.text.clone());
+                } else {
+                    on_deromanize(&line.finish());
+                    warning.clear();
+                    warned = None;
+                    
+                    history.push(&line.text);
+                    history_index = history.len();
+                    
+                    line.set("");
+                }
             }
             Key::Char(ch) => {
-                if pos == text.len() {
-                    text.push(ch);
-                    pos += ch.len_utf8();
-                }
+                line.push(ch);
             },
             Key::Ctrl(c) => {
                 if c == 'c' {
@@ -124,52 +304,51 @@ pub fn start_interactive_loop<F: FnMut(&str)>(initial_message: &str, prompt: &st
             },
             // Cursor movement
             Key::Left => {
-                // move back one translated character
+                line.left();
             },
             Key::Right => {
-                
+                line.right();
             },
             Key::Up => {
                 if history_index > 0 {
                     history_index -= 1;
-                    text = history.get(history_index).unwrap().to_string();
-                    pos = text.len();
+                    line.set(history.get(history_index).unwrap());
                 }
             },
             Key::Down => {
                 if history_index + 1 >= history.len() {
-                    text.clear();
-                    pos = text.len();
+                    line.set("");
                     if history_index < history.len() {
                         history_index = history.len();
                     }
                 } else {
                     history_index += 1;
-                    text = history.get(history_index).unwrap().to_string();
-                    pos = text.len();
+                    line.set(history.get(history_index).unwrap());
                 }
             },
-            // Delete back one source character
+            // Delete back one jamo
             Key::Backspace => {
-                if ! text.is_empty() {
-                    if pos == text.len() {
-                        text.pop();
-                        pos -= 1;
-                    } else {
-                        // TODO    
-                    }
-                }
+                line.backspace();
             },
             _ => {}
         }
+        if warned.as_ref().is_some_and(|text| *text != line.text) {
+            warning.clear();
+            warned = None;
+        }
         
         write!(stdout,
-            "{}{}{}{}",
+            "{}{}{}{}{}{}{}",
             termion::cursor::Goto(1, 3),
             termion::clear::AfterCursor,
+            termion::cursor::Goto(1, 4),
+            warning,
+            termion::cursor::Goto(1, 3),
             prompt,
-            deromanize_escaped(&text),
+            line.hangeul(),
         ).unwrap();
+        let column = 1 + width(prompt) + line.cursor_column();
+        write!(stdout, "{}", termion::cursor::Goto(column as u16, 3)).unwrap();
         
         stdout.flush().unwrap();
     }
@@ -178,3 +357,53 @@ pub fn start_interactive_loop<F: FnMut(&str)>(initial_message: &str, prompt: &st
         termion::cursor::Show,
     ).unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Line;
+    use super::super::{EscapeConfig, InputScheme};
+
+    #[test]
+    fn cursor_movement() {
+        let escapes = EscapeConfig::default();
+        let mut line = Line::new(&InputScheme::Dero, &escapes, false);
+        line.set("dalgi");
+        line.left();
+        assert_eq!(line.cursor, 3);
+        line.left();
+        line.left();
+        assert_eq!(line.cursor, 0);
+        line.right();
+        assert_eq!(line.cursor, 3);
+        assert_eq!(line.cursor_column(), 2);
+        line.push('a');
+        assert_eq!(line.text, "dalagi");
+        assert_eq!(line.hangeul(), "다라기");
+        line.backspace();
+        assert_eq!(line.text, "dalgi");
+        assert_eq!(line.cursor, 3);
+        line.right();
+        line.right();
+        line.push('n');
+        assert_eq!(line.hangeul(), "달긴");
+    }
+
+    #[test]
+    fn marked_line() {
+        let escapes = EscapeConfig::marked();
+        let mut line = Line::new(&InputScheme::Dero, &escapes, true);
+        for ch in "My name is {{jeimsy".chars() {
+            line.push(ch);
+        }
+        assert_eq!(line.hangeul(), "My name is 제임스");
+        line.backspace();
+        assert_eq!(line.hangeul(), "My name is 제임ㅅ");
+        for ch in "y}}.".chars() {
+            line.push(ch);
+        }
+        assert_eq!(line.finish(), "My name is 제임스.");
+        let mut escaped = Line::new(&InputScheme::Dero, &escapes, false);
+        escaped.set("ga {{ga}}");
+        assert_eq!(escaped.finish(), "가 ga");
+    }
+}
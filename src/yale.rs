@@ -0,0 +1,178 @@
+//! Conversion from 한글 to the Yale romanization.
+//!
+//! Yale transliterates the spelling rather than the pronunciation, so every
+//! jamo is written the same way regardless of its neighbours (`읽다` =>
+//! `ilkta`, `좋다` => `cohta`). A period separates letters that would
+//! otherwise be read as a single consonant (`각하` => `kak.ha`).
+
+use hangeul2::{Initial, Vowel, Final, Block};
+
+fn initial_letters(ini: Initial) -> &'static str {
+    use hangeul2::Initial::*;
+    match ini {
+        G     => "k",
+        Gg    => "kk",
+        N     => "n",
+        D     => "t",
+        Dd    => "tt",
+        R     => "l",
+        M     => "m",
+        B     => "p",
+        Bb    => "pp",
+        S     => "s",
+        Ss    => "ss",
+        Ieung => "",
+        J     => "c",
+        Jj    => "cc",
+        Ch    => "ch",
+        K     => "kh",
+        T     => "th",
+        P     => "ph",
+        H     => "h",
+    }
+}
+
+fn vowel_letters(vow: Vowel) -> &'static str {
+    use hangeul2::Vowel::*;
+    match vow {
+        A   => "a",
+        Ae  => "ay",
+        Ya  => "ya",
+        Yae => "yay",
+        Eo  => "e",
+        E   => "ey",
+        Yeo => "ye",
+        Ye  => "yey",
+        O   => "o",
+        Wa  => "wa",
+        Wae => "way",
+        Oe  => "oy",
+        Yo  => "yo",
+        U   => "wu",
+        Weo => "we",
+        We  => "wey",
+        Wi  => "wi",
+        Yu  => "yu",
+        Y   => "u",
+        Yi  => "uy",
+        I   => "i",
+    }
+}
+
+fn final_letters(fin: Final) -> &'static str {
+    use hangeul2::Final::*;
+    match fin {
+        Empty => "",
+        G     => "k",
+        Gg    => "kk",
+        Gs    => "ks",
+        N     => "n",
+        Nj    => "nc",
+        Nh    => "nh",
+        D     => "t",
+        L     => "l",
+        Lg    => "lk",
+        Lm    => "lm",
+        Lb    => "lp",
+        Ls    => "ls",
+        Lt    => "lth",
+        Lp    => "lph",
+        Lh    => "lh",
+        M     => "m",
+        B     => "p",
+        Bs    => "ps",
+        S     => "s",
+        Ss    => "ss",
+        Ieung => "ng",
+        J     => "c",
+        Ch    => "ch",
+        K     => "kh",
+        T     => "th",
+        P     => "ph",
+        H     => "h",
+    }
+}
+
+/// Returns whether the last letter of a final and the first letter of the
+/// following initial would be read as a single consonant.
+fn is_ambiguous(fin: &str, ini: &str) -> bool {
+    match (fin.chars().last(), ini.chars().next()) {
+        (Some('k'), Some('h')) | (Some('t'), Some('h')) |
+        (Some('p'), Some('h')) | (Some('c'), Some('h')) => true,
+        (Some(last), Some(first)) => last == first && "ktpsc".contains(last),
+        _ => false,
+    }
+}
+
+/// Converts the 한글 in the given text to the Yale romanization.
+/// Characters that are not 한글 syllables are left untouched.
+///
+/// Ex: `한국어` => `hankwuke`
+pub fn romanize(text: &str) -> String {
+    let mut s = String::new();
+    romanize_into(text, &mut s);
+    s
+}
+
+/// Converts the 한글 in the given text to the Yale romanization.
+/// See `romanize`.
+pub fn romanize_into(text: &str, s: &mut String) {
+    use hangeul2::Initial::*;
+    let mut prev = "";
+    for ch in text.chars() {
+        let block = if let Some(block) = Block::from_char(ch) {
+            block
+        } else {
+            s.push(ch);
+            prev = "";
+            continue;
+        };
+        let initial = initial_letters(block.initial);
+        if is_ambiguous(prev, initial) {
+            s.push('.');
+        }
+        s.push_str(initial);
+        // ㅜ is written without its 'w' after labial consonants
+        match (block.initial, block.vowel) {
+            (M, Vowel::U) | (B, Vowel::U) | (Bb, Vowel::U) | (P, Vowel::U) => s.push('u'),
+            (_, vow) => s.push_str(vowel_letters(vow)),
+        }
+        prev = final_letters(block.fin);
+        s.push_str(prev);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::romanize;
+
+    #[test]
+    fn examples() {
+        let examples = [
+            ("한글", "hankul"),
+            ("한국어", "hankwuke"),
+            ("서울", "sewul"),
+            ("부산", "pusan"),
+            ("물", "mul"),
+            ("김치", "kimchi"),
+            ("좋다", "cohta"),
+            ("읽다", "ilkta"),
+            ("없다", "epsta"),
+            ("값", "kaps"),
+            ("밖에", "pakkey"),
+            ("의사", "uysa"),
+            ("괜찮아요", "kwaynchanhayo"),
+        ];
+        for &(hangeul, expected) in &examples {
+            assert_eq!(romanize(hangeul), expected, "{}", hangeul);
+        }
+    }
+
+    #[test]
+    fn ambiguous_letters_are_separated() {
+        assert_eq!(romanize("각하"), "kak.ha");
+        assert_eq!(romanize("가카"), "kakha");
+        assert_eq!(romanize("낙곡"), "nak.kok");
+        assert_eq!(romanize("앗싸"), "as.ssa");
+    }
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "tui")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs 'dero filter' with the given arguments and returns its exit code and stderr
+fn filter(args: &[&str]) -> (Option<i32>, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dero"))
+        .arg("filter")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Could not run dero");
+    // A rejected filter may exit before reading anything
+    let _ = child.stdin.take().unwrap().write_all(b"ga\n");
+    let output = child.wait_with_output().unwrap();
+    (output.status.code(), String::from_utf8(output.stderr).unwrap())
+}
+
+#[test]
+fn filter_converts_stdin() {
+    assert_eq!(filter(&[]).0, Some(0));
+}
+
+#[test]
+fn filter_rejects_unused_options() {
+    let cases: &[(&[&str], &str)] = &[
+        (&["hangyl"],                "TEXT"),
+        (&["-"],                     "'-' or --stdin"),
+        (&["--input", "in.txt"],     "--input"),
+        (&["--output", "out.txt"],   "--output"),
+        (&["--markdown", "in.md"],   "--markdown"),
+        (&["--escape-char", "x"],    "--escape-char"),
+    ];
+    for &(args, name) in cases {
+        let (code, stderr) = filter(args);
+        assert_eq!(code, Some(2), "{:?}", args);
+        assert_eq!(stderr, format!("{} cannot be used with 'dero filter'\n", name));
+    }
+}